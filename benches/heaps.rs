@@ -0,0 +1,334 @@
+//! Benchmarks of `FibonacciHeap` against `PairingHeap`, `IndexedBinaryHeap` and
+//! `std::collections::BinaryHeap`, on a few typical workloads. Run them with
+//!
+//! ```sh
+//! cargo bench --bench heaps
+//! ```
+//!
+//! Besides the timings that criterion reports, every benchmark prints the number of heap
+//! allocations per operation, as counted by a global allocator.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use fibonacci_heap_rust::{
+    AddressablePriorityQueue, BinomialHeap, FibonacciHeap, IndexedBinaryHeap, PairingHeap,
+};
+
+#[path = "../src/tests/support/rng.rs"]
+mod rng;
+
+use rng::Rng;
+
+/// The system allocator, but counting the number of allocations.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Runs `f` once, and prints the number of allocations it made per operation.
+fn report_allocations<R>(name: &str, ops: usize, f: impl FnOnce() -> R) {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    black_box(f());
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+    println!(
+        "{name}: {:.3} allocations/op",
+        allocations as f64 / ops as f64
+    );
+}
+
+fn random_keys(n: usize) -> Vec<u64> {
+    let mut rng = Rng(0x2545f4914f6cdd1d);
+    (0..n).map(|_| rng.next() % 1_000_000).collect()
+}
+
+const N: usize = 10_000;
+
+fn bench_push(c: &mut Criterion) {
+    let keys = random_keys(N);
+    let mut group = c.benchmark_group("push");
+    group.throughput(Throughput::Elements(N as u64));
+    group.bench_function("FibonacciHeap", |b| {
+        b.iter(|| {
+            let mut heap = FibonacciHeap::new();
+            for &key in &keys {
+                heap.push(key);
+            }
+            heap
+        })
+    });
+    group.bench_function("BinaryHeap", |b| {
+        b.iter(|| {
+            let mut heap = BinaryHeap::new();
+            for &key in &keys {
+                heap.push(Reverse(key));
+            }
+            heap
+        })
+    });
+    group.bench_function("PairingHeap", |b| {
+        b.iter(|| {
+            let mut heap = PairingHeap::new();
+            for &key in &keys {
+                heap.push(key);
+            }
+            heap
+        })
+    });
+    group.finish();
+    report_allocations("push/FibonacciHeap", N, || {
+        let mut heap = FibonacciHeap::new();
+        for &key in &keys {
+            heap.push(key);
+        }
+        heap
+    });
+}
+
+/// Pushes `N` keys, and then pops and pushes alternately, `N` times each.
+fn bench_push_pop(c: &mut Criterion) {
+    let keys = random_keys(2 * N);
+    let fibonacci = || {
+        let mut heap = FibonacciHeap::from(keys[..N].to_vec());
+        for &key in &keys[N..] {
+            black_box(heap.pop());
+            heap.push(key);
+        }
+        heap
+    };
+    let mut group = c.benchmark_group("push_pop");
+    group.throughput(Throughput::Elements(3 * N as u64));
+    group.bench_function("FibonacciHeap", |b| b.iter(fibonacci));
+    group.bench_function("BinaryHeap", |b| {
+        b.iter(|| {
+            let mut heap: BinaryHeap<_> = keys[..N].iter().map(|&key| Reverse(key)).collect();
+            for &key in &keys[N..] {
+                black_box(heap.pop());
+                heap.push(Reverse(key));
+            }
+            heap
+        })
+    });
+    group.bench_function("PairingHeap", |b| {
+        b.iter(|| {
+            let mut heap = PairingHeap::from(keys[..N].to_vec());
+            for &key in &keys[N..] {
+                black_box(heap.pop());
+                heap.push(key);
+            }
+            heap
+        })
+    });
+    group.finish();
+    report_allocations("push_pop/FibonacciHeap", 3 * N, fibonacci);
+}
+
+/// A random directed graph with `n` vertices, each with `degree` outgoing edges, as adjacency lists
+/// of `(target, weight)` pairs.
+fn random_graph(n: usize, degree: usize) -> Vec<Vec<(usize, u64)>> {
+    let mut rng = Rng(0x9e3779b97f4a7c15);
+    (0..n)
+        .map(|_| {
+            (0..degree)
+                .map(|_| (rng.below(n), rng.next() % 1000 + 1))
+                .collect()
+        })
+        .collect()
+}
+
+/// Dijkstra with decrease_key, on any addressable priority queue.
+fn dijkstra<Q: AddressablePriorityQueue<Item = (u64, usize)> + Default>(
+    graph: &[Vec<(usize, u64)>],
+) -> Vec<u64> {
+    let mut dist = vec![u64::MAX; graph.len()];
+    let mut handles: Vec<Option<Q::Handle>> = vec![None; graph.len()];
+    let mut heap = Q::default();
+    dist[0] = 0;
+    handles[0] = Some(heap.push((0, 0)));
+    while let Some((d, u)) = heap.pop() {
+        for &(v, w) in &graph[u] {
+            if d + w < dist[v] {
+                dist[v] = d + w;
+                match &handles[v] {
+                    Some(handle) => heap.decrease_key(handle, (d + w, v)),
+                    None => handles[v] = Some(heap.push((d + w, v))),
+                }
+            }
+        }
+    }
+    dist
+}
+
+/// Dijkstra without decrease_key: improved distances are pushed again, and outdated entries are
+/// skipped when they are popped.
+fn dijkstra_binary(graph: &[Vec<(usize, u64)>]) -> Vec<u64> {
+    let mut dist = vec![u64::MAX; graph.len()];
+    let mut heap = BinaryHeap::new();
+    dist[0] = 0;
+    heap.push(Reverse((0, 0)));
+    while let Some(Reverse((d, u))) = heap.pop() {
+        if d > dist[u] {
+            continue;
+        }
+        for &(v, w) in &graph[u] {
+            if d + w < dist[v] {
+                dist[v] = d + w;
+                heap.push(Reverse((d + w, v)));
+            }
+        }
+    }
+    dist
+}
+
+fn bench_dijkstra(c: &mut Criterion) {
+    let graph = random_graph(N, 16);
+    let edges = graph.len() * 16;
+    let expected = dijkstra_binary(&graph);
+    assert_eq!(dijkstra::<FibonacciHeap<_>>(&graph), expected);
+    assert_eq!(dijkstra::<PairingHeap<_>>(&graph), expected);
+    assert_eq!(dijkstra::<BinomialHeap<_>>(&graph), expected);
+    assert_eq!(dijkstra::<IndexedBinaryHeap<_>>(&graph), expected);
+    let mut group = c.benchmark_group("dijkstra");
+    group.throughput(Throughput::Elements(edges as u64));
+    group.bench_function("FibonacciHeap", |b| {
+        b.iter(|| dijkstra::<FibonacciHeap<_>>(&graph))
+    });
+    group.bench_function("BinaryHeap", |b| b.iter(|| dijkstra_binary(&graph)));
+    group.bench_function("PairingHeap", |b| {
+        b.iter(|| dijkstra::<PairingHeap<_>>(&graph))
+    });
+    group.bench_function("BinomialHeap", |b| {
+        b.iter(|| dijkstra::<BinomialHeap<_>>(&graph))
+    });
+    group.bench_function("IndexedBinaryHeap", |b| {
+        b.iter(|| dijkstra::<IndexedBinaryHeap<_>>(&graph))
+    });
+    group.finish();
+    report_allocations("dijkstra/FibonacciHeap (per edge)", edges, || {
+        dijkstra::<FibonacciHeap<_>>(&graph)
+    });
+    report_allocations("dijkstra/PairingHeap (per edge)", edges, || {
+        dijkstra::<PairingHeap<_>>(&graph)
+    });
+}
+
+/// Melds two heaps of `N` elements each, both of which have been consolidated by a pop.
+fn bench_meld(c: &mut Criterion) {
+    let keys = random_keys(2 * N);
+    let mut group = c.benchmark_group("meld");
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("FibonacciHeap", |b| {
+        b.iter_batched(
+            || {
+                let mut heap1 = FibonacciHeap::from(keys[..N].to_vec());
+                let mut heap2 = FibonacciHeap::from(keys[N..].to_vec());
+                heap1.pop();
+                heap2.pop();
+                (heap1, heap2)
+            },
+            |(heap1, heap2)| FibonacciHeap::from_meld(heap1, heap2),
+            BatchSize::LargeInput,
+        )
+    });
+    group.bench_function("PairingHeap", |b| {
+        b.iter_batched(
+            || {
+                let mut heap1 = PairingHeap::from(keys[..N].to_vec());
+                let mut heap2 = PairingHeap::from(keys[N..].to_vec());
+                heap1.pop();
+                heap2.pop();
+                (heap1, heap2)
+            },
+            |(heap1, heap2)| PairingHeap::from_meld(heap1, heap2),
+            BatchSize::LargeInput,
+        )
+    });
+    group.bench_function("BinaryHeap", |b| {
+        b.iter_batched(
+            || {
+                let heap1: BinaryHeap<_> = keys[..N].iter().map(|&key| Reverse(key)).collect();
+                let heap2: BinaryHeap<_> = keys[N..].iter().map(|&key| Reverse(key)).collect();
+                (heap1, heap2)
+            },
+            |(mut heap1, mut heap2)| {
+                heap1.append(&mut heap2);
+                heap1
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+/// Drops a heap of `N` elements that has been consolidated by a pop.
+fn bench_drop(c: &mut Criterion) {
+    let keys = random_keys(N);
+    let mut group = c.benchmark_group("drop");
+    group.throughput(Throughput::Elements(N as u64));
+    group.bench_function("FibonacciHeap", |b| {
+        b.iter_batched(
+            || {
+                let mut heap = FibonacciHeap::from(keys.clone());
+                heap.pop();
+                heap
+            },
+            drop,
+            BatchSize::LargeInput,
+        )
+    });
+    group.bench_function("PairingHeap", |b| {
+        b.iter_batched(
+            || {
+                let mut heap = PairingHeap::from(keys.clone());
+                heap.pop();
+                heap
+            },
+            drop,
+            BatchSize::LargeInput,
+        )
+    });
+    group.bench_function("BinaryHeap", |b| {
+        b.iter_batched(
+            || {
+                keys.iter()
+                    .map(|&key| Reverse(key))
+                    .collect::<BinaryHeap<_>>()
+            },
+            drop,
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_push,
+    bench_push_pop,
+    bench_dijkstra,
+    bench_meld,
+    bench_drop
+);
+criterion_main!(benches);
@@ -0,0 +1,193 @@
+//! Interprets the fuzzer input as a script of operations on a few `FibonacciHeap`s, and checks the
+//! heap invariants (and the popped elements, against a simple model) after every step. Run it with
+//! AddressSanitizer to find memory bugs in the unsafe code:
+//!
+//! ```sh
+//! cargo +nightly fuzz run heap_ops
+//! ```
+//!
+//! Handles are kept around after their element is popped or deleted, or after their heap is
+//! dropped, and are then used on any of the heaps. Using a handle to an element that is still in
+//! some *other* heap must panic, and leave both heaps unchanged.
+
+#![no_main]
+
+use std::collections::BTreeSet;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use arbitrary::Arbitrary;
+use fibonacci_heap_rust::{FibonacciHeap, NodePtr};
+use libfuzzer_sys::fuzz_target;
+
+/// The number of heaps that the script operates on.
+const HEAPS: usize = 3;
+
+/// A key, along with the index of the handle that was returned when it was pushed. The index makes
+/// every key unique, so that we know exactly which element should be popped.
+type Key = (u8, usize);
+
+#[derive(Arbitrary, Debug)]
+enum Op {
+    Push { heap: u8, value: u8 },
+    PushMany { heap: u8, values: Vec<u8> },
+    Pop { heap: u8 },
+    DecreaseKey { heap: u8, handle: u16, value: u8 },
+    Delete { heap: u8, handle: u16 },
+    Meld { into: u8, from: u8 },
+    Drop { heap: u8 },
+}
+
+struct State {
+    heaps: [FibonacciHeap<Key>; HEAPS],
+    /// The keys that should be in each heap.
+    models: [BTreeSet<Key>; HEAPS],
+    /// All handles ever returned, along with the current key of their element and the heap that
+    /// it is in (if it is still in some heap).
+    handles: Vec<(NodePtr<Key>, Key, Option<usize>)>,
+}
+
+impl State {
+    fn push(&mut self, heap: usize, value: u8) {
+        let key = (value, self.handles.len());
+        let handle = self.heaps[heap].push(key);
+        self.models[heap].insert(key);
+        self.handles.push((handle, key, Some(heap)));
+    }
+
+    fn apply(&mut self, op: Op) {
+        match op {
+            Op::Push { heap, value } => self.push(heap as usize % HEAPS, value),
+            Op::PushMany { heap, values } => {
+                for value in values {
+                    self.push(heap as usize % HEAPS, value);
+                }
+            }
+            Op::Pop { heap } => {
+                let heap = heap as usize % HEAPS;
+                let expected = self.models[heap].pop_first();
+                if let Some((_, id)) = expected {
+                    self.handles[id].2 = None;
+                }
+                assert_eq!(self.heaps[heap].pop(), expected);
+            }
+            Op::DecreaseKey {
+                heap,
+                handle,
+                value,
+            } => {
+                let heap = heap as usize % HEAPS;
+                let Some(id) = self.pick(handle) else {
+                    return;
+                };
+                if self.is_foreign(heap, id) {
+                    let handle = self.handles[id].0.clone();
+                    self.check_foreign(heap, id, |heaps| {
+                        heaps[heap].decrease_key(&handle, (value, id))
+                    });
+                    return;
+                }
+                let (handle, old_key, location) = &mut self.handles[id];
+                let new_key = (value, id);
+                self.heaps[heap].decrease_key(handle, new_key);
+                if location.is_some() && new_key < *old_key {
+                    self.models[heap].remove(old_key);
+                    self.models[heap].insert(new_key);
+                    *old_key = new_key;
+                }
+            }
+            Op::Delete { heap, handle } => {
+                let heap = heap as usize % HEAPS;
+                let Some(id) = self.pick(handle) else {
+                    return;
+                };
+                if self.is_foreign(heap, id) {
+                    let handle = self.handles[id].0.clone();
+                    self.check_foreign(heap, id, |heaps| {
+                        let _ = heaps[heap].delete(handle);
+                    });
+                    return;
+                }
+                let (handle, key, location) = &mut self.handles[id];
+                let expected = location.take().map(|_| *key);
+                if let Some(key) = expected {
+                    self.models[heap].remove(&key);
+                }
+                assert_eq!(self.heaps[heap].delete(handle.clone()), expected);
+            }
+            Op::Meld { into, from } => {
+                let (into, from) = (into as usize % HEAPS, from as usize % HEAPS);
+                if into == from {
+                    return;
+                }
+                let heap1 = std::mem::take(&mut self.heaps[into]);
+                let heap2 = std::mem::take(&mut self.heaps[from]);
+                self.heaps[into] = FibonacciHeap::from_meld(heap1, heap2);
+                for key in std::mem::take(&mut self.models[from]) {
+                    self.handles[key.1].2 = Some(into);
+                    self.models[into].insert(key);
+                }
+            }
+            Op::Drop { heap } => {
+                let heap = heap as usize % HEAPS;
+                self.heaps[heap] = FibonacciHeap::new();
+                for (_, id) in std::mem::take(&mut self.models[heap]) {
+                    self.handles[id].2 = None;
+                }
+            }
+        }
+    }
+
+    /// Picks the handle with index `handle` (modulo the number of handles).
+    fn pick(&self, handle: u16) -> Option<usize> {
+        if self.handles.is_empty() {
+            return None;
+        }
+        Some(handle as usize % self.handles.len())
+    }
+
+    /// Returns whether the element of handle `id` is still in a heap other than `heap`.
+    fn is_foreign(&self, heap: usize, id: usize) -> bool {
+        self.handles[id].2.is_some_and(|location| location != heap)
+    }
+
+    /// Runs `f`, which uses the handle `id` on `heap` although its element is in another heap, and
+    /// checks that it panics without changing either heap.
+    fn check_foreign(&mut self, heap: usize, id: usize, f: impl FnOnce(&mut [FibonacciHeap<Key>])) {
+        let owner = self.handles[id].2.unwrap();
+        // the panic hook of libfuzzer aborts the process, so it is replaced while `f` runs
+        let hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(|_| {}));
+        let result = catch_unwind(AssertUnwindSafe(|| f(&mut self.heaps)));
+        std::panic::set_hook(hook);
+        assert!(result.is_err());
+        for heap in [heap, owner] {
+            self.heaps[heap].check_invariants();
+            assert_eq!(self.heaps[heap].len(), self.models[heap].len());
+            assert_eq!(self.heaps[heap].peek(), self.models[heap].first());
+        }
+    }
+
+    fn check(&self) {
+        for (heap, model) in self.heaps.iter().zip(&self.models) {
+            heap.check_invariants();
+            assert_eq!(heap.len(), model.len());
+        }
+    }
+}
+
+fuzz_target!(|ops: Vec<Op>| {
+    let mut state = State {
+        heaps: Default::default(),
+        models: Default::default(),
+        handles: vec![],
+    };
+    for op in ops {
+        state.apply(op);
+        state.check();
+    }
+    // pop some of the elements, and leave the rest to `Drop`
+    let [heap, ..] = &mut state.heaps;
+    for expected in std::mem::take(&mut state.models[0]).into_iter().take(100) {
+        assert_eq!(heap.pop(), Some(expected));
+    }
+});
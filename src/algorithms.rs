@@ -0,0 +1,81 @@
+//! Graph algorithms built on [FibonacciHeap](crate::FibonacciHeap), which is where its O(1)
+//! amortized `decrease_key` actually pays off.
+//!
+//! [dijkstra] and [prim] work on any graph that implements the [Graph] trait. Vertices are
+//! numbered `0..graph.vertex_count()`, and adjacency lists (`Vec<Vec<(usize, W)>>`, where every
+//! entry is a `(target, weight)` pair) implement the trait out of the box. [astar] explores graphs
+//! that are given implicitly, by a function that returns the neighbors of a node.
+
+mod astar;
+mod dijkstra;
+mod prim;
+
+pub use astar::astar;
+pub use dijkstra::{dijkstra, dijkstra_to, ShortestPaths};
+pub use prim::{prim, SpanningForest};
+
+/// A weighted directed graph, whose vertices are numbered `0..vertex_count()`.
+///
+/// An undirected graph is represented by listing every edge in both directions.
+pub trait Graph {
+    /// The type of the edge weights.
+    type Weight: Weight;
+
+    /// Returns the number of vertices of the graph.
+    fn vertex_count(&self) -> usize;
+
+    /// Returns the outgoing edges of `vertex`, as `(target, weight)` pairs.
+    fn neighbors(&self, vertex: usize) -> impl Iterator<Item = (usize, Self::Weight)> + '_;
+}
+
+/// The type of an edge weight (or a path length) in a [Graph].
+pub trait Weight: Copy + Ord + std::ops::Add<Output = Self> {
+    /// The length of the empty path.
+    const ZERO: Self;
+}
+
+macro_rules! impl_weight {
+    ($($t:ty),*) => {
+        $(impl Weight for $t {
+            const ZERO: $t = 0;
+        })*
+    };
+}
+
+impl_weight!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+impl<W: Weight> Graph for [Vec<(usize, W)>] {
+    type Weight = W;
+
+    fn vertex_count(&self) -> usize {
+        self.len()
+    }
+
+    fn neighbors(&self, vertex: usize) -> impl Iterator<Item = (usize, W)> + '_ {
+        self[vertex].iter().copied()
+    }
+}
+
+impl<W: Weight> Graph for Vec<Vec<(usize, W)>> {
+    type Weight = W;
+
+    fn vertex_count(&self) -> usize {
+        self.len()
+    }
+
+    fn neighbors(&self, vertex: usize) -> impl Iterator<Item = (usize, W)> + '_ {
+        self[vertex].iter().copied()
+    }
+}
+
+impl<G: Graph + ?Sized> Graph for &G {
+    type Weight = G::Weight;
+
+    fn vertex_count(&self) -> usize {
+        (**self).vertex_count()
+    }
+
+    fn neighbors(&self, vertex: usize) -> impl Iterator<Item = (usize, G::Weight)> + '_ {
+        (**self).neighbors(vertex)
+    }
+}
@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use super::Weight;
+use crate::{FibonacciHeap, NodePtr};
+
+/// Finds a shortest path from `start` to `goal` with the A* algorithm, and returns it (including
+/// `start` and `goal`) along with its length, or `None` if `goal` cannot be reached.
+///
+/// Unlike the other algorithms in this module, the graph does not have to be known in advance:
+/// `neighbors(node)` returns the outgoing edges of `node` as `(neighbor, weight)` pairs, and is only
+/// called for nodes that are actually visited. All weights must be non-negative.
+///
+/// `heuristic(node)` must never overestimate the length of a shortest path from `node` to `goal`.
+/// It should also be *consistent*: `heuristic(a) <= weight + heuristic(b)` for every edge from `a`
+/// to `b` with weight `weight`. Then, every node is expanded at most once. With a heuristic that is
+/// not consistent, nodes are expanded again whenever a shorter path to them is found, so the
+/// result is still correct. In debug builds however, this function panics as soon as it finds an
+/// edge for which the heuristic is not consistent, because that is usually a bug.
+///
+/// # Examples
+///
+/// ```
+/// use fibonacci_heap_rust::algorithms::astar;
+///
+/// // walk from (0, 0) to (3, 2) on an empty grid
+/// let neighbors = |&(x, y): &(i32, i32)| {
+///     [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)].map(|next| (next, 1))
+/// };
+/// let manhattan = |&(x, y): &(i32, i32)| (3 - x).abs() + (2 - y).abs();
+/// let (path, cost) = astar((0, 0), (3, 2), neighbors, manhattan).unwrap();
+/// assert_eq!(cost, 5);
+/// assert_eq!(path.len(), 6);
+/// ```
+pub fn astar<N, W, I>(
+    start: N,
+    goal: N,
+    mut neighbors: impl FnMut(&N) -> I,
+    mut heuristic: impl FnMut(&N) -> W,
+) -> Option<(Vec<N>, W)>
+where
+    N: Eq + Hash + Clone,
+    W: Weight,
+    I: IntoIterator<Item = (N, W)>,
+{
+    // All nodes seen so far get an index, and everything we know about them is stored in `nodes`.
+    let mut indices = HashMap::new();
+    let mut nodes = vec![];
+    let mut heap = FibonacciHeap::new();
+    indices.insert(start.clone(), 0);
+    let estimate = heuristic(&start);
+    nodes.push(NodeInfo {
+        handle: heap.push((estimate, 0)),
+        node: start,
+        distance: W::ZERO,
+        estimate,
+        predecessor: None,
+        closed: false,
+    });
+    while let Some((_, index)) = heap.pop() {
+        if nodes[index].node == goal {
+            let distance = nodes[index].distance;
+            let mut path = vec![];
+            let mut current = Some(index);
+            while let Some(index) = current {
+                path.push(nodes[index].node.clone());
+                current = nodes[index].predecessor;
+            }
+            path.reverse();
+            return Some((path, distance));
+        }
+        nodes[index].closed = true;
+        for (neighbor, weight) in neighbors(&nodes[index].node) {
+            debug_assert!(weight >= W::ZERO, "negative edge weight");
+            let distance = nodes[index].distance + weight;
+            let neighbor_index = match indices.get(&neighbor) {
+                Some(&neighbor_index) => neighbor_index,
+                None => {
+                    let neighbor_index = nodes.len();
+                    indices.insert(neighbor.clone(), neighbor_index);
+                    let estimate = heuristic(&neighbor);
+                    let key = (distance + estimate, neighbor_index);
+                    nodes.push(NodeInfo {
+                        handle: heap.push(key),
+                        node: neighbor,
+                        distance,
+                        estimate,
+                        predecessor: Some(index),
+                        closed: false,
+                    });
+                    check_consistency(&nodes[index], weight, &nodes[neighbor_index]);
+                    continue;
+                }
+            };
+            check_consistency(&nodes[index], weight, &nodes[neighbor_index]);
+            let info = &mut nodes[neighbor_index];
+            if info.distance <= distance {
+                continue;
+            }
+            info.distance = distance;
+            info.predecessor = Some(index);
+            let key = (distance + info.estimate, neighbor_index);
+            if info.closed {
+                // only possible if the heuristic is not consistent: the node has to be expanded
+                // again, and its old handle has been invalidated when it was popped
+                info.closed = false;
+                info.handle = heap.push(key);
+            } else {
+                heap.decrease_key(&info.handle, key);
+            }
+        }
+    }
+    None
+}
+
+/// Everything A* knows about a node.
+struct NodeInfo<N, W> {
+    node: N,
+    /// The length of the shortest path to this node found so far.
+    distance: W,
+    /// The value of the heuristic for this node.
+    estimate: W,
+    predecessor: Option<usize>,
+    /// The handle to the entry of this node in the heap, whose key is `(distance + estimate, index)`.
+    handle: NodePtr<(W, usize)>,
+    /// Whether this node has been expanded (and not reopened since).
+    closed: bool,
+}
+
+/// In debug builds, panics if the heuristic is not consistent for the edge from `from` to `to`.
+fn check_consistency<N, W: Weight>(from: &NodeInfo<N, W>, weight: W, to: &NodeInfo<N, W>) {
+    debug_assert!(
+        from.estimate <= weight + to.estimate,
+        "the heuristic of astar is not consistent"
+    );
+}
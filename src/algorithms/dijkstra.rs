@@ -0,0 +1,114 @@
+use super::{Graph, Weight};
+use crate::{FibonacciHeap, NodePtr};
+
+/// The result of [dijkstra] or [dijkstra_to]: the distances from the source to the other vertices,
+/// and a shortest path tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShortestPaths<W> {
+    /// For every vertex, the length of a shortest path from the source to it, or `None` if it is
+    /// unreachable (or if it was not reached before the search stopped at the target).
+    pub distances: Vec<Option<W>>,
+    /// For every vertex, its predecessor on a shortest path from the source. This is `None` for the
+    /// source itself and for vertices that were not reached.
+    pub predecessors: Vec<Option<usize>>,
+}
+
+impl<W> ShortestPaths<W> {
+    /// Returns the vertices on a shortest path from the source to `target` (both included), or
+    /// `None` if `target` was not reached.
+    pub fn path_to(&self, target: usize) -> Option<Vec<usize>> {
+        self.distances[target].as_ref()?;
+        let mut path = vec![target];
+        while let Some(predecessor) = self.predecessors[*path.last().unwrap()] {
+            path.push(predecessor);
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// Computes the shortest paths from `source` to all other vertices of `graph`, in
+/// O(E + V log V) time. All edge weights must be non-negative.
+///
+/// # Examples
+///
+/// ```
+/// use fibonacci_heap_rust::algorithms::dijkstra;
+///
+/// // 0 --5--> 1 --1--> 2, and 0 --7--> 2
+/// let graph = vec![vec![(1, 5), (2, 7)], vec![(2, 1)], vec![], vec![]];
+/// let paths = dijkstra(&graph, 0);
+/// assert_eq!(paths.distances, vec![Some(0), Some(5), Some(6), None]);
+/// assert_eq!(paths.path_to(2), Some(vec![0, 1, 2]));
+/// assert_eq!(paths.path_to(3), None);
+/// ```
+pub fn dijkstra<G: Graph>(graph: G, source: usize) -> ShortestPaths<G::Weight> {
+    search(graph, source, None)
+}
+
+/// Like [dijkstra], but stops as soon as the shortest path to `target` is known. The distances and
+/// predecessors of `source`, `target`, and all vertices on the path between them are final;
+/// vertices that were not reached yet have distance `None`.
+///
+/// # Examples
+///
+/// ```
+/// use fibonacci_heap_rust::algorithms::dijkstra_to;
+///
+/// let graph = vec![vec![(1, 2)], vec![(2, 2)], vec![(3, 2)], vec![]];
+/// let paths = dijkstra_to(&graph, 0, 1);
+/// assert_eq!(paths.distances[1], Some(2));
+/// assert_eq!(paths.distances[3], None); // never reached
+/// ```
+pub fn dijkstra_to<G: Graph>(graph: G, source: usize, target: usize) -> ShortestPaths<G::Weight> {
+    search(graph, source, Some(target))
+}
+
+/// A handle to a `(distance, vertex)` entry of the heap.
+type Handle<W> = NodePtr<(W, usize)>;
+
+fn search<G: Graph>(graph: G, source: usize, target: Option<usize>) -> ShortestPaths<G::Weight> {
+    let n = graph.vertex_count();
+    let mut distances = vec![None; n];
+    let mut predecessors = vec![None; n];
+    // Handles to the heap entries of the vertices that have been reached. Once a vertex is popped,
+    // its handle is invalidated and decrease_key on it does nothing, but that never happens: its
+    // distance is final, so no shorter path to it will be found.
+    let mut handles: Vec<Option<Handle<G::Weight>>> = vec![None; n];
+    // whether the distance of a vertex is final, because it has been popped
+    let mut settled = vec![false; n];
+    let mut heap = FibonacciHeap::new();
+    distances[source] = Some(G::Weight::ZERO);
+    handles[source] = Some(heap.push((G::Weight::ZERO, source)));
+    while let Some((distance, vertex)) = heap.pop() {
+        settled[vertex] = true;
+        if Some(vertex) == target {
+            break;
+        }
+        for (neighbor, weight) in graph.neighbors(vertex) {
+            debug_assert!(weight >= G::Weight::ZERO, "negative edge weight");
+            let new_distance = distance + weight;
+            if distances[neighbor].is_some_and(|old| old <= new_distance) {
+                continue;
+            }
+            distances[neighbor] = Some(new_distance);
+            predecessors[neighbor] = Some(vertex);
+            match &handles[neighbor] {
+                Some(handle) => heap.decrease_key(handle, (new_distance, neighbor)),
+                None => handles[neighbor] = Some(heap.push((new_distance, neighbor))),
+            }
+        }
+    }
+    if !heap.is_empty() {
+        // we stopped at the target, and the vertices that are still in the heap only have a
+        // tentative distance
+        for vertex in (0..n).filter(|&vertex| !settled[vertex]) {
+            distances[vertex] = None;
+            predecessors[vertex] = None;
+        }
+    }
+    ShortestPaths {
+        distances,
+        predecessors,
+    }
+}
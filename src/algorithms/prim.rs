@@ -0,0 +1,81 @@
+use super::{Graph, Weight};
+use crate::{FibonacciHeap, NodePtr};
+
+/// The result of [prim]: a minimum spanning forest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpanningForest<W> {
+    /// The edges of the forest, as `(from, to, weight)` triples. There is one edge less than there
+    /// are vertices in every connected component.
+    pub edges: Vec<(usize, usize, W)>,
+    /// The sum of the weights of all edges.
+    pub total_weight: W,
+}
+
+/// Computes a minimum spanning forest of `graph` with Prim's algorithm, in O(E + V log V) time. If
+/// the graph is connected, this is a minimum spanning tree.
+///
+/// The graph is treated as undirected, so every edge should be listed in both directions (if an
+/// edge is only listed in one direction, it is only found from that side, which may lead to a
+/// forest that is not minimal, or has more trees than necessary).
+///
+/// # Examples
+///
+/// ```
+/// use fibonacci_heap_rust::algorithms::prim;
+///
+/// // a triangle 0-1-2, and a separate edge 3-4
+/// let graph = vec![
+///     vec![(1, 1), (2, 3)],
+///     vec![(0, 1), (2, 2)],
+///     vec![(0, 3), (1, 2)],
+///     vec![(4, 5)],
+///     vec![(3, 5)],
+/// ];
+/// let forest = prim(&graph);
+/// assert_eq!(forest.edges, vec![(0, 1, 1), (1, 2, 2), (3, 4, 5)]);
+/// assert_eq!(forest.total_weight, 8);
+/// ```
+pub fn prim<G: Graph>(graph: G) -> SpanningForest<G::Weight> {
+    let n = graph.vertex_count();
+    let mut in_forest = vec![false; n];
+    // for every vertex that is not in the forest yet: the lightest known edge connecting it to the
+    // forest, as `(from, weight)`
+    let mut best_edges: Vec<Option<(usize, G::Weight)>> = vec![None; n];
+    let mut handles: Vec<Option<Handle<G::Weight>>> = vec![None; n];
+    let mut heap = FibonacciHeap::new();
+    let mut edges = vec![];
+    let mut total_weight = G::Weight::ZERO;
+    for root in 0..n {
+        if in_forest[root] {
+            continue;
+        }
+        // grow a new tree from `root`, which has no connecting edge
+        heap.push((G::Weight::ZERO, root));
+        while let Some((_, vertex)) = heap.pop() {
+            in_forest[vertex] = true;
+            if let Some((from, weight)) = best_edges[vertex] {
+                edges.push((from, vertex, weight));
+                total_weight = total_weight + weight;
+            }
+            for (neighbor, weight) in graph.neighbors(vertex) {
+                if in_forest[neighbor]
+                    || best_edges[neighbor].is_some_and(|(_, best)| best <= weight)
+                {
+                    continue;
+                }
+                best_edges[neighbor] = Some((vertex, weight));
+                match &handles[neighbor] {
+                    Some(handle) => heap.decrease_key(handle, (weight, neighbor)),
+                    None => handles[neighbor] = Some(heap.push((weight, neighbor))),
+                }
+            }
+        }
+    }
+    SpanningForest {
+        edges,
+        total_weight,
+    }
+}
+
+/// A handle to a `(weight, vertex)` entry of the heap.
+type Handle<W> = NodePtr<(W, usize)>;
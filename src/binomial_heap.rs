@@ -0,0 +1,508 @@
+use crate::{HeapTag, Shared};
+
+/// A min-binomial heap, with the same handles as [FibonacciHeap](crate::FibonacciHeap): pushing
+/// an element returns a [BinomialPtr], which can be used to decrease its key or to delete it, and
+/// which keeps track of whether the element is still in the heap.
+///
+/// Like on an [IndexedBinaryHeap](crate::IndexedBinaryHeap), every operation takes O(log n) time in
+/// the *worst case*, but this is the only heap in this crate that also melds in worst-case O(log n)
+/// time. The heap is a list of at most log₂(n) + 1 trees of distinct orders, so no `pop` ever has
+/// to consolidate a long list of roots like a [FibonacciHeap](crate::FibonacciHeap) does after many
+/// pushes, and no `decrease_key` ever causes a long chain of cascading cuts. This makes it a good
+/// choice when latency matters more than throughput. `push` still takes O(1) amortized time, and
+/// `peek` takes O(1) time. The price is that `decrease_key` takes O(log n) time instead of O(1)
+/// amortized, since the new key moves up towards the root (a strict Fibonacci heap would avoid
+/// that, but with much larger constants).
+///
+/// Since keys move between the nodes of a tree, a [BinomialPtr] follows its element around, so
+/// handles work exactly like they do for the other heaps.
+///
+/// Like [FibonacciHeap](crate::FibonacciHeap), the heap is left in a consistent state if the `Ord`
+/// or `Drop` implementation of `T` panics, and the only thing that may be off afterwards is the
+/// next element that is popped.
+///
+/// # Examples
+///
+/// ```
+/// use fibonacci_heap_rust::BinomialHeap;
+///
+/// let mut heap = BinomialHeap::new();
+/// heap.push(5);
+/// let seven = heap.push(7);
+/// let three = heap.push(3);
+/// heap.decrease_key(&seven, 1);
+/// assert_eq!(heap.delete(three), Some(3));
+/// assert_eq!(heap.pop(), Some(1));
+/// assert_eq!(heap.pop(), Some(5));
+/// assert_eq!(heap.pop(), None);
+/// ```
+pub struct BinomialHeap<T> {
+    /// The current number of nodes in the heap.
+    n: usize,
+    /// `trees[k]` is the root of the tree of order k (which has 2^k nodes), or a null pointer if
+    /// there is no such tree. The last entry is never null.
+    trees: Vec<*mut Node<T>>,
+    /// Trees that still have to be added to `trees`. This is empty, except in the middle of an
+    /// operation and after a comparison panicked. Trees are added from the back, and the tree at
+    /// the back may be the result of linking some trees that were added before.
+    pending: Vec<*mut Node<T>>,
+    /// The minimal root, or a null pointer if the heap is empty. This always points to a root in
+    /// `trees` or `pending`, but if a comparison panicked, not necessarily to the minimal one.
+    min: *mut Node<T>,
+    /// The tag that identifies this heap. It is created when the first element is pushed.
+    tag: Option<Shared<HeapTag>>,
+}
+
+/// A handle to an element inside a [BinomialHeap], which works just like a
+/// [NodePtr](crate::NodePtr).
+pub struct BinomialPtr<T>(Shared<BinomialPtrInternal<T>>);
+
+struct BinomialPtrInternal<T> {
+    /// Whether the element has been popped or deleted, or the heap has been dropped.
+    invalidated: bool,
+    /// The node that currently holds the element.
+    ptr: *mut Node<T>,
+    /// The tag of the heap which this element was pushed into.
+    heap_tag: Shared<HeapTag>,
+}
+
+/// A node in a binomial tree. A node of order k has k children, of orders k - 1 down to 0, in
+/// this order.
+struct Node<T> {
+    key: T,
+    /// The parent of this node, or a null pointer if it is a root.
+    parent: *mut Node<T>,
+    /// The first child of this node, which has the highest order, or a null pointer if it has no
+    /// children.
+    child: *mut Node<T>,
+    /// The next child of the parent of this node, or a null pointer if this is the last one.
+    sibling: *mut Node<T>,
+    /// The order of the subtree of this node, which is its number of children.
+    order: usize,
+    /// The handle of the element in this node. When keys move to other nodes, their handles move
+    /// along with them.
+    outside_ref: Shared<BinomialPtrInternal<T>>,
+}
+
+impl<T> Clone for BinomialPtr<T> {
+    fn clone(&self) -> BinomialPtr<T> {
+        BinomialPtr(self.0.clone())
+    }
+}
+
+impl<T: Ord> BinomialHeap<T> {
+    /// Constructs a new, empty binomial heap.
+    pub const fn new() -> BinomialHeap<T> {
+        BinomialHeap {
+            n: 0,
+            trees: Vec::new(),
+            pending: Vec::new(),
+            min: std::ptr::null_mut(),
+            tag: None,
+        }
+    }
+
+    /// Checks whether the heap is empty.
+    pub fn is_empty(&self) -> bool {
+        self.min.is_null()
+    }
+
+    /// Returns the number of elements in the heap.
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    /// Returns a reference to the minimum element of the heap, or `None` if it is empty.
+    pub fn peek(&self) -> Option<&T> {
+        if self.min.is_null() {
+            None
+        } else {
+            unsafe { Some(&(*self.min).key) }
+        }
+    }
+
+    /// Produces a binomial heap from melding two existing binomial heaps, in O(log n) time. The
+    /// [BinomialPtr]s pointing to elements of either heap can be used with the new heap.
+    pub fn from_meld(mut heap1: BinomialHeap<T>, mut heap2: BinomialHeap<T>) -> BinomialHeap<T> {
+        heap1.absorb(&mut heap2);
+        heap1
+    }
+
+    /// Moves all elements of `other` into this heap in O(log n) time, leaving `other` empty.
+    ///
+    /// If the comparison of the two minimums panics, neither heap is changed.
+    pub(crate) fn absorb(&mut self, other: &mut BinomialHeap<T>) {
+        if other.is_empty() {
+            return;
+        } else if self.is_empty() {
+            std::mem::swap(self, other);
+            return;
+        }
+        // compare before moving anything, in case the comparison panics
+        let other_has_min = unsafe { (*other.min).key < (*self.min).key };
+        self.pending.append(&mut other.pending);
+        // add the trees of `other` from the smallest to the largest, like adding binary numbers
+        self.pending
+            .extend(other.trees.drain(..).rev().filter(|tree| !tree.is_null()));
+        if other_has_min {
+            self.min = other.min;
+        }
+        self.n += other.n;
+        self.tag = HeapTag::union(self.tag.take(), other.tag.take());
+        // the nodes now belong to `self`, so `other` must not free them when it is dropped
+        other.min = std::ptr::null_mut();
+        other.n = 0;
+        unsafe { self.add_pending() };
+    }
+
+    /// Inserts an element into the heap, and returns a handle to it.
+    pub fn push(&mut self, item: T) -> BinomialPtr<T> {
+        // compare before allocating anything, in case the comparison panics
+        let is_new_min = self.min.is_null() || unsafe { item < (*self.min).key };
+        let node: *mut Node<T> = Box::into_raw(Box::new(Node {
+            key: item,
+            parent: std::ptr::null_mut(),
+            child: std::ptr::null_mut(),
+            sibling: std::ptr::null_mut(),
+            order: 0,
+            outside_ref: Shared::new(BinomialPtrInternal {
+                invalidated: false,
+                ptr: std::ptr::null_mut(),
+                heap_tag: self.tag.get_or_insert_with(HeapTag::new).clone(),
+            }),
+        }));
+        unsafe {
+            (*node).outside_ref.borrow_mut().ptr = node;
+            let handle = BinomialPtr((*node).outside_ref.clone());
+            self.pending.push(node);
+            if is_new_min {
+                self.min = node;
+            }
+            self.n += 1;
+            self.add_pending();
+            handle
+        }
+    }
+
+    /// Decreases the key of the element that `elem` points to, in O(log n) time. If this element is
+    /// not in the heap anymore, or if `new_key` is larger than its key, nothing happens.
+    ///
+    /// Panics if `elem` points to an element of another heap.
+    pub fn decrease_key(&mut self, elem: &BinomialPtr<T>, new_key: T) {
+        let Some(node) = self.node_of(elem, "decrease_key") else {
+            return;
+        };
+        unsafe {
+            if new_key > (*node).key {
+                return;
+            }
+            // find the node that the new key moves up to, and do all comparisons before changing
+            // anything, in case one of them panics
+            let mut target = node;
+            while !(*target).parent.is_null() && new_key < (*(*target).parent).key {
+                target = (*target).parent;
+            }
+            let is_new_min =
+                (*target).parent.is_null() && target != self.min && new_key < (*self.min).key;
+            let old_key = std::mem::replace(&mut (*node).key, new_key);
+            let mut node = node;
+            while node != target {
+                BinomialHeap::swap_with_parent(node);
+                node = (*node).parent;
+            }
+            if is_new_min {
+                self.min = target;
+            }
+            drop(old_key);
+        }
+    }
+
+    /// Deletes the element that `elem` points to, and returns it, in O(log n) time. If this element
+    /// is not in the heap anymore, nothing happens and `None` is returned.
+    ///
+    /// Panics if `elem` points to an element of another heap.
+    pub fn delete(&mut self, elem: BinomialPtr<T>) -> Option<T> {
+        let node = self.node_of(&elem, "delete")?;
+        unsafe {
+            self.add_pending();
+            // move the element up to the root of its tree, as if its key were the smallest one
+            let mut node = node;
+            while !(*node).parent.is_null() {
+                BinomialHeap::swap_with_parent(node);
+                node = (*node).parent;
+            }
+            Some(self.remove_root(node))
+        }
+    }
+
+    /// Extracts the minimum element from the heap and returns it, in O(log n) time.
+    pub fn pop(&mut self) -> Option<T> {
+        if self.min.is_null() {
+            return None;
+        }
+        unsafe {
+            self.add_pending();
+            Some(self.remove_root(self.min))
+        }
+    }
+
+    /// Removes `root`, which must be in `trees`, adds its children to the heap, and returns its
+    /// key.
+    unsafe fn remove_root(&mut self, root: *mut Node<T>) -> T {
+        self.trees[(*root).order] = std::ptr::null_mut();
+        while self.trees.last().is_some_and(|tree| tree.is_null()) {
+            self.trees.pop();
+        }
+        // the children come in decreasing order, so they are added from the smallest to the
+        // largest, like adding binary numbers
+        let mut child = (*root).child;
+        while !child.is_null() {
+            (*child).parent = std::ptr::null_mut();
+            self.pending.push(child);
+            let next = (*child).sibling;
+            (*child).sibling = std::ptr::null_mut();
+            child = next;
+        }
+        (*root).outside_ref.borrow_mut().invalidated = true;
+        // The root is not in the heap anymore, so take ownership of it before adding its children:
+        // if a comparison panics, it is then freed while unwinding.
+        let root = Box::from_raw(root);
+        self.n -= 1;
+        // `min` must point to some root, even if one of the comparisons below panics
+        self.min = self
+            .trees
+            .iter()
+            .chain(&self.pending)
+            .copied()
+            .find(|tree| !tree.is_null())
+            .unwrap_or(std::ptr::null_mut());
+        self.add_pending();
+        let mut min = self.min;
+        for &tree in &self.trees {
+            if !tree.is_null() && (*tree).key < (*min).key {
+                min = tree;
+            }
+        }
+        self.min = min;
+        root.key
+    }
+
+    /// Adds the pending trees to `trees`, linking trees of the same order, like the carries when
+    /// adding binary numbers.
+    ///
+    /// Every tree is in `trees` or in `pending` at all times, so if a comparison panics, the heap
+    /// is still consistent, and the remaining trees are added by the next operation.
+    unsafe fn add_pending(&mut self) {
+        while let Some(&tree) = self.pending.last() {
+            let order = (*tree).order;
+            if order >= self.trees.len() {
+                self.trees.resize(order + 1, std::ptr::null_mut());
+            }
+            let other = self.trees[order];
+            if other.is_null() {
+                self.trees[order] = tree;
+                self.pending.pop();
+                continue;
+            }
+            // compare before relinking anything, in case the comparison panics
+            let (winner, loser) = if (*other).key < (*tree).key {
+                (other, tree)
+            } else {
+                (tree, other)
+            };
+            self.trees[order] = std::ptr::null_mut();
+            (*loser).parent = winner;
+            (*loser).sibling = (*winner).child;
+            (*winner).child = loser;
+            (*winner).order += 1;
+            if self.min == loser {
+                self.min = winner;
+            }
+            *self.pending.last_mut().unwrap() = winner;
+        }
+        while self.trees.last().is_some_and(|tree| tree.is_null()) {
+            self.trees.pop();
+        }
+    }
+
+    /// Swaps the element in `node` with the one in its parent, along with their handles.
+    unsafe fn swap_with_parent(node: *mut Node<T>) {
+        let parent = (*node).parent;
+        std::mem::swap(&mut (*node).key, &mut (*parent).key);
+        std::mem::swap(&mut (*node).outside_ref, &mut (*parent).outside_ref);
+        (*node).outside_ref.borrow_mut().ptr = node;
+        (*parent).outside_ref.borrow_mut().ptr = parent;
+    }
+
+    /// Returns a pointer to the node that `elem` points to, or `None` if that element is not in
+    /// any heap anymore. Panics if the element is in some other heap than this one.
+    fn node_of(&self, elem: &BinomialPtr<T>, method: &str) -> Option<*mut Node<T>> {
+        let (node, heap_tag) = {
+            let elem = elem.0.borrow();
+            if elem.invalidated {
+                return None;
+            }
+            (elem.ptr, elem.heap_tag.clone())
+        };
+        if !HeapTag::belongs_to(&heap_tag, self.tag.as_ref()) {
+            panic!(
+                "Oh no... you called some_heap.{method}(..) on \
+                an element that was never inserted into this heap!"
+            )
+        }
+        Some(node)
+    }
+}
+
+#[cfg(test)]
+impl<T: Ord> BinomialHeap<T> {
+    /// Returns the number of trees in the heap.
+    pub(crate) fn tree_count(&self) -> usize {
+        self.trees.iter().filter(|tree| !tree.is_null()).count() + self.pending.len()
+    }
+
+    /// Panics if the heap is not a valid binomial heap, if some trees are still pending, or if
+    /// `min` is not the minimal root.
+    pub(crate) fn check_invariants(&self) {
+        self.check_structure();
+        assert!(self.pending.is_empty(), "some trees are still pending");
+        for &tree in &self.trees {
+            if !tree.is_null() {
+                unsafe { assert!((*tree).key >= (*self.min).key, "`min` is not minimal") };
+            }
+        }
+    }
+
+    /// Panics if the heap is not a valid binomial heap, except that some trees may still be
+    /// pending, and `min` does not have to point to the minimal root (which are both allowed after
+    /// a comparison panicked).
+    pub(crate) fn check_structure(&self) {
+        assert!(!self.trees.last().is_some_and(|tree| tree.is_null()));
+        let mut count = 0;
+        let mut min_is_root = false;
+        let roots = self
+            .trees
+            .iter()
+            .enumerate()
+            .filter(|(_, tree)| !tree.is_null());
+        let roots = roots.map(|(order, &tree)| (tree, Some(order)));
+        let roots = roots.chain(self.pending.iter().map(|&tree| (tree, None)));
+        for (root, order) in roots {
+            unsafe {
+                assert!((*root).parent.is_null() && (*root).sibling.is_null());
+                if let Some(order) = order {
+                    assert_eq!((*root).order, order, "tree in the wrong place");
+                }
+                min_is_root |= root == self.min;
+                let mut stack = vec![root];
+                while let Some(node) = stack.pop() {
+                    let outside_ref = (*node).outside_ref.borrow();
+                    assert!(std::ptr::eq(outside_ref.ptr, node) && !outside_ref.invalidated);
+                    count += 1;
+                    assert!(count <= self.n, "more nodes than `n`");
+                    // the children must have the orders k - 1, ..., 0
+                    let mut child = (*node).child;
+                    for order in (0..(*node).order).rev() {
+                        assert!(!child.is_null(), "too few children");
+                        assert_eq!((*child).order, order, "child of the wrong order");
+                        assert_eq!((*child).parent, node, "wrong parent");
+                        assert!((*child).key >= (*node).key, "heap order violated");
+                        stack.push(child);
+                        child = (*child).sibling;
+                    }
+                    assert!(child.is_null(), "too many children");
+                }
+            }
+        }
+        assert_eq!(min_is_root, count > 0, "`min` is not a root");
+        assert_eq!(count, self.n);
+    }
+}
+
+impl<T> Drop for BinomialHeap<T> {
+    fn drop(&mut self) {
+        /// Frees `node`, after pushing its next sibling and its first child onto `stack`.
+        unsafe fn free_node<T>(stack: &mut Vec<*mut Node<T>>, node: *mut Node<T>) {
+            if !(*node).sibling.is_null() {
+                stack.push((*node).sibling);
+            }
+            if !(*node).child.is_null() {
+                stack.push((*node).child);
+            }
+            let node = Box::from_raw(node);
+            node.outside_ref.borrow_mut().invalidated = true;
+        }
+
+        /// If dropping a key panics, this guard frees the remaining nodes while unwinding.
+        struct DropGuard<'a, T>(&'a mut Vec<*mut Node<T>>);
+
+        impl<T> Drop for DropGuard<'_, T> {
+            fn drop(&mut self) {
+                while let Some(node) = self.0.pop() {
+                    unsafe { free_node(self.0, node) };
+                }
+            }
+        }
+
+        // the trees are only O(log n) deep, but the stack is needed for the siblings anyway
+        let mut stack: Vec<*mut Node<T>> = std::mem::take(&mut self.pending);
+        stack.extend(self.trees.iter().filter(|tree| !tree.is_null()));
+        while let Some(node) = stack.pop() {
+            let guard = DropGuard(&mut stack);
+            unsafe { free_node(guard.0, node) };
+            std::mem::forget(guard);
+        }
+    }
+}
+
+impl<T: Ord, const N: usize> From<[T; N]> for BinomialHeap<T> {
+    /// Constructs a binomial heap from an array of items.
+    fn from(elems: [T; N]) -> Self {
+        let mut heap = BinomialHeap::new();
+        for elem in elems {
+            heap.push(elem);
+        }
+        heap
+    }
+}
+
+impl<T: Ord> From<Vec<T>> for BinomialHeap<T> {
+    /// Constructs a binomial heap from a `Vec` of items.
+    fn from(elems: Vec<T>) -> Self {
+        let mut heap = BinomialHeap::new();
+        for elem in elems {
+            heap.push(elem);
+        }
+        heap
+    }
+}
+
+impl<T: Ord> From<BinomialHeap<T>> for Vec<T> {
+    /// Constructs a `Vec` of items from a `BinomialHeap`. The items in the `Vec` are sorted in
+    /// increasing order (minimal element first). The binomial heap is consumed.
+    fn from(mut heap: BinomialHeap<T>) -> Vec<T> {
+        let mut res = vec![];
+        while let Some(popped) = heap.pop() {
+            res.push(popped);
+        }
+        res
+    }
+}
+
+impl<T: Ord> Default for BinomialHeap<T> {
+    /// Creates an empty `BinomialHeap<T>`.
+    fn default() -> BinomialHeap<T> {
+        BinomialHeap::new()
+    }
+}
+
+// SAFETY: just like for `FibonacciHeap` and `NodePtr`, the nodes are only accessed through the
+// heap that owns them, and everything that is shared with the handles is behind `Arc<Mutex<..>>`s.
+#[cfg(feature = "sync")]
+unsafe impl<T: Send> Send for BinomialHeap<T> {}
+#[cfg(feature = "sync")]
+unsafe impl<T: Sync> Sync for BinomialHeap<T> {}
+#[cfg(feature = "sync")]
+unsafe impl<T: Send> Send for BinomialPtr<T> {}
+#[cfg(feature = "sync")]
+unsafe impl<T: Send> Sync for BinomialPtr<T> {}
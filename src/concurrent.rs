@@ -0,0 +1,174 @@
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+use crate::FibonacciHeap;
+
+/// A Fibonacci heap that can be shared between many producer and consumer threads.
+///
+/// Pushing an element does not touch the main heap at all: every producer thread has its own
+/// pending heap (threads are spread over a fixed number of pending heaps, about one per CPU), and
+/// its elements are simply added to the root list of that heap. Whenever a consumer pops, it melds
+/// all pending heaps into the main heap first, which takes O(1) time per pending heap because it
+/// only concatenates the root lists. So producers only ever contend with other producers that
+/// share the same pending heap, and never have to wait for a consumer that is consolidating the
+/// main heap.
+///
+/// This type is only available with the `sync` feature enabled.
+///
+/// # Examples
+///
+/// ```
+/// use fibonacci_heap_rust::ConcurrentFibonacciHeap;
+/// use std::sync::Arc;
+/// use std::thread;
+/// use std::time::Duration;
+///
+/// let heap = Arc::new(ConcurrentFibonacciHeap::new());
+/// let producers: Vec<_> = (0..4)
+///     .map(|i| {
+///         let heap = Arc::clone(&heap);
+///         thread::spawn(move || heap.push(i))
+///     })
+///     .collect();
+///
+/// let mut popped = vec![];
+/// while popped.len() < 4 {
+///     popped.extend(heap.pop_wait(Duration::from_secs(10)));
+/// }
+/// popped.sort();
+/// assert_eq!(popped, vec![0, 1, 2, 3]);
+///
+/// for producer in producers {
+///     producer.join().unwrap();
+/// }
+/// assert_eq!(heap.pop(), None);
+/// ```
+pub struct ConcurrentFibonacciHeap<T> {
+    /// The main heap, which consumers pop from.
+    heap: Mutex<FibonacciHeap<T>>,
+    /// The heaps that producers push into. They are melded into the main heap by the consumers.
+    pending: Box<[Mutex<FibonacciHeap<T>>]>,
+    /// Signalled when an element is pushed while some consumer is waiting in `pop_wait`.
+    available: Condvar,
+    /// The number of consumers that are currently in `pop_wait`.
+    waiting: AtomicUsize,
+}
+
+impl<T: Ord> ConcurrentFibonacciHeap<T> {
+    /// Constructs a new, empty concurrent Fibonacci heap.
+    pub fn new() -> ConcurrentFibonacciHeap<T> {
+        let shards = std::thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        ConcurrentFibonacciHeap {
+            heap: Mutex::new(FibonacciHeap::new()),
+            pending: (0..shards)
+                .map(|_| Mutex::new(FibonacciHeap::new()))
+                .collect(),
+            available: Condvar::new(),
+            waiting: AtomicUsize::new(0),
+        }
+    }
+
+    /// Insert an element into the heap.
+    ///
+    /// This only locks the pending heap of the current thread, unless some consumer is waiting for
+    /// an element in [pop_wait](ConcurrentFibonacciHeap::pop_wait), in which case it has to be
+    /// woken up.
+    pub fn push(&self, item: T) {
+        lock(&self.pending[pending_index() % self.pending.len()]).push(item);
+        if self.waiting.load(Ordering::SeqCst) > 0 {
+            // A consumer may be just about to start waiting. Taking the lock of the main heap makes
+            // sure that it is either still checking the pending heaps (and will find our element),
+            // or already waiting (and will be woken up by us).
+            drop(lock(&self.heap));
+            self.available.notify_one();
+        }
+    }
+
+    /// Extracts the minimum element from the heap and returns it, or returns `None` if the heap is
+    /// empty.
+    pub fn pop(&self) -> Option<T> {
+        let mut heap = lock(&self.heap);
+        self.meld_pending(&mut heap);
+        heap.pop()
+    }
+
+    /// Extracts the minimum element from the heap and returns it. If the heap is empty, this waits
+    /// until some other thread pushes an element, for at most `timeout`. If the heap is still empty
+    /// after that, `None` is returned.
+    pub fn pop_wait(&self, timeout: Duration) -> Option<T> {
+        /// Counts a consumer as waiting until it is dropped, even if `T::cmp` panics while popping.
+        struct Waiting<'a>(&'a AtomicUsize);
+
+        impl Drop for Waiting<'_> {
+            fn drop(&mut self) {
+                self.0.fetch_sub(1, Ordering::SeqCst);
+            }
+        }
+
+        let deadline = Instant::now().checked_add(timeout);
+        let mut heap = lock(&self.heap);
+        // from now on, every producer will take the lock before notifying us (see `push`)
+        self.waiting.fetch_add(1, Ordering::SeqCst);
+        // dropped before `heap`, so while we still hold the lock
+        let _waiting = Waiting(&self.waiting);
+        loop {
+            self.meld_pending(&mut heap);
+            if let Some(popped) = heap.pop() {
+                return Some(popped);
+            }
+            let remaining = match deadline {
+                Some(deadline) => match deadline.checked_duration_since(Instant::now()) {
+                    Some(remaining) if !remaining.is_zero() => remaining,
+                    _ => return None,
+                },
+                None => Duration::MAX,
+            };
+            heap = self
+                .available
+                .wait_timeout(heap, remaining)
+                .unwrap_or_else(PoisonError::into_inner)
+                .0;
+        }
+    }
+
+    /// Melds all pending heaps into the main heap.
+    fn meld_pending(&self, heap: &mut FibonacciHeap<T>) {
+        for pending in self.pending.iter() {
+            heap.absorb(&mut lock(pending));
+        }
+    }
+}
+
+#[cfg(test)]
+impl<T> ConcurrentFibonacciHeap<T> {
+    /// Returns the number of consumers that producers think are waiting in `pop_wait`.
+    pub(crate) fn waiting(&self) -> usize {
+        self.waiting.load(Ordering::SeqCst)
+    }
+}
+
+impl<T: Ord> Default for ConcurrentFibonacciHeap<T> {
+    /// Creates an empty `ConcurrentFibonacciHeap<T>`.
+    fn default() -> ConcurrentFibonacciHeap<T> {
+        ConcurrentFibonacciHeap::new()
+    }
+}
+
+/// Locks a mutex. If some thread panicked while holding it, we keep using the heap anyway: a panic
+/// (for example in `T::cmp`) never leaves a heap in an inconsistent state.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Returns the index of the pending heap that the current thread pushes into (modulo the number of
+/// pending heaps). Threads get consecutive indices, so that they are spread evenly over the
+/// pending heaps.
+fn pending_index() -> usize {
+    static NEXT_INDEX: AtomicUsize = AtomicUsize::new(0);
+    thread_local! {
+        static INDEX: usize = NEXT_INDEX.fetch_add(1, Ordering::Relaxed);
+    }
+    INDEX.with(|index| *index)
+}
@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::{FibonacciHeap, NodePtr};
+
+/// The event queue of a discrete-event simulation: events are scheduled at some time, and are
+/// taken out of the queue in order of time by [advance](EventQueue::advance).
+///
+/// Events that are scheduled at the same time come out in the order in which they were scheduled.
+/// Every event gets a sequence number when it is scheduled, and the heap is keyed by
+/// `(time, sequence number)`.
+///
+/// The time type `T` can be anything that is `Ord` and `Copy`, such as an integer number of ticks
+/// (the default) or a [Duration](std::time::Duration) since the start of the simulation.
+///
+/// # Examples
+///
+/// ```
+/// use fibonacci_heap_rust::EventQueue;
+///
+/// let mut queue = EventQueue::new();
+/// queue.schedule(10, "timeout");
+/// let reply = queue.schedule(20, "reply");
+/// queue.schedule(5, "send");
+/// queue.reschedule_earlier(&reply, 7);
+///
+/// assert_eq!(queue.advance(), Some((5, "send")));
+/// assert_eq!(queue.advance(), Some((7, "reply")));
+/// assert_eq!(queue.now(), Some(7));
+/// assert_eq!(queue.advance(), Some((10, "timeout")));
+/// assert_eq!(queue.advance(), None);
+/// ```
+pub struct EventQueue<E, T = u64> {
+    heap: FibonacciHeap<(T, u64)>,
+    /// The events that are still scheduled, along with their times, by sequence number.
+    events: HashMap<u64, (T, E)>,
+    /// The sequence number of the next event that is scheduled.
+    next_seq: u64,
+    /// The time of the last event returned by `advance`.
+    now: Option<T>,
+    /// A number that is unique for every queue, to recognize handles of other queues.
+    id: u64,
+}
+
+/// A handle to an event in an [EventQueue], which can be used to reschedule or cancel it.
+pub struct EventHandle<T = u64> {
+    ptr: NodePtr<(T, u64)>,
+    seq: u64,
+    queue_id: u64,
+}
+
+impl<E, T: Ord + Copy> EventQueue<E, T> {
+    /// Constructs a new, empty event queue.
+    pub fn new() -> EventQueue<E, T> {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        EventQueue {
+            heap: FibonacciHeap::new(),
+            events: HashMap::new(),
+            next_seq: 0,
+            now: None,
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+
+    /// Returns the number of scheduled events.
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    /// Checks whether there are no scheduled events.
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// Returns the time of the last event that was returned by [advance](EventQueue::advance), or
+    /// `None` if no event has been returned yet.
+    pub fn now(&self) -> Option<T> {
+        self.now
+    }
+
+    /// Returns the time of the next event, without taking it out of the queue.
+    pub fn peek_time(&self) -> Option<T> {
+        self.heap.peek().map(|&(at, _)| at)
+    }
+
+    /// Schedules `event` at time `at`, and returns a handle to it.
+    ///
+    /// Panics if `at` is before the current time (see [now](EventQueue::now)).
+    pub fn schedule(&mut self, at: T, event: E) -> EventHandle<T> {
+        self.check_not_in_past(at);
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.events.insert(seq, (at, event));
+        EventHandle {
+            ptr: self.heap.push((at, seq)),
+            seq,
+            queue_id: self.id,
+        }
+    }
+
+    /// Moves the event that `handle` points to to the earlier time `at`. Returns false (and does
+    /// nothing) if the event is not scheduled anymore, or if `at` is not earlier than its current
+    /// time.
+    ///
+    /// The event keeps its sequence number, so among events at time `at`, it comes out in the order
+    /// in which it was originally scheduled.
+    ///
+    /// Panics if `at` is before the current time, or if `handle` belongs to another queue.
+    pub fn reschedule_earlier(&mut self, handle: &EventHandle<T>, at: T) -> bool {
+        self.check_not_in_past(at);
+        self.check_handle(handle);
+        match self.events.get_mut(&handle.seq) {
+            Some((time, _)) if at < *time => {
+                self.heap.decrease_key(&handle.ptr, (at, handle.seq));
+                *time = at;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Cancels the event that `handle` points to, and returns its time and the event itself, or
+    /// `None` if it is not scheduled anymore.
+    ///
+    /// Panics if `handle` belongs to another queue.
+    pub fn cancel(&mut self, handle: EventHandle<T>) -> Option<(T, E)> {
+        self.check_handle(&handle);
+        let (_, seq) = self.heap.delete(handle.ptr)?;
+        self.events.remove(&seq)
+    }
+
+    /// Takes the next event out of the queue, and returns it along with its time, which becomes
+    /// the current time. Returns `None` if there are no events left.
+    pub fn advance(&mut self) -> Option<(T, E)> {
+        let (at, seq) = self.heap.pop()?;
+        self.now = Some(at);
+        self.events.remove(&seq)
+    }
+
+    fn check_not_in_past(&self, at: T) {
+        if self.now.is_some_and(|now| at < now) {
+            panic!("cannot schedule an event before the current time of the event queue");
+        }
+    }
+
+    fn check_handle(&self, handle: &EventHandle<T>) {
+        if handle.queue_id != self.id {
+            panic!("this event handle belongs to another event queue");
+        }
+    }
+}
+
+impl<T> Clone for EventHandle<T> {
+    fn clone(&self) -> EventHandle<T> {
+        EventHandle {
+            ptr: self.ptr.clone(),
+            seq: self.seq,
+            queue_id: self.queue_id,
+        }
+    }
+}
+
+impl<E, T: Ord + Copy> Default for EventQueue<E, T> {
+    /// Creates an empty `EventQueue<E, T>`.
+    fn default() -> EventQueue<E, T> {
+        EventQueue::new()
+    }
+}
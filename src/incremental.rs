@@ -0,0 +1,141 @@
+use crate::{FibonacciHeap, Node};
+
+/// Consolidating the root list a little at a time, so that `pop` does not have to do it all at
+/// once.
+///
+/// A Fibonacci heap only consolidates its root list when popping, so after n pushes, the next pop
+/// takes O(n) time. This is fine for the total running time, but not for the latency of a single
+/// pop. With these methods, the linking can be done earlier, in bounded steps: either explicitly
+/// with [consolidate_step](FibonacciHeap::consolidate_step) (for example while the program is
+/// idle), or after every push, decrease_key and meld, with
+/// [set_consolidation_budget](FibonacciHeap::set_consolidation_budget). Either way, the heap ends up
+/// with the same kind of trees as if it had been consolidated by a pop.
+impl<T: Ord> FibonacciHeap<T> {
+    /// Does at most `budget` units of consolidation work, and returns whether the root list is
+    /// fully consolidated now (no two roots have the same degree), so that the next pop only has
+    /// O(log n) roots to go through besides the children of the minimum.
+    ///
+    /// One unit of work is looking at a root, or linking two roots. The step continues where the
+    /// previous one stopped, so calling this repeatedly with a small budget is as good as calling
+    /// it once with a large one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fibonacci_heap_rust::FibonacciHeap;
+    ///
+    /// let mut heap = FibonacciHeap::new();
+    /// for i in 0..1000 {
+    ///     heap.push(i);
+    /// }
+    /// // link the 1000 roots a bit at a time, before anything is popped
+    /// while !heap.consolidate_step(100) {}
+    /// assert_eq!(heap.pop(), Some(0));
+    /// ```
+    pub fn consolidate_step(&mut self, mut budget: usize) -> bool {
+        unsafe {
+            while self.unsettled > 0 && budget > 0 {
+                budget -= 1;
+                let mut x = self.cursor;
+                if x.is_null() || !(*x).parent.is_null() {
+                    // the root where the last step stopped has been linked since then
+                    x = self.min;
+                }
+                let mut d = (*x).degree;
+                if self.degrees.get(d) == Some(&x) {
+                    self.cursor = (*x).right;
+                    continue;
+                }
+                // link `x` with the settled roots of the same degree, like the carries when
+                // adding binary numbers
+                loop {
+                    if d >= self.degrees.len() {
+                        self.degrees.resize(d + 1, std::ptr::null_mut());
+                    }
+                    let y = self.degrees[d];
+                    if y.is_null() {
+                        self.degrees[d] = x;
+                        self.settled += 1;
+                        self.unsettled -= 1;
+                        break;
+                    }
+                    if budget == 0 {
+                        // `x` stays unsettled, and the next step starts with it
+                        break;
+                    }
+                    budget -= 1;
+                    // compare before relinking anything, in case the comparison panics
+                    let (parent, child) = if (*x).key > (*y).key { (y, x) } else { (x, y) };
+                    self.degrees[d] = std::ptr::null_mut();
+                    self.settled -= 1;
+                    self.link(parent, child);
+                    x = parent;
+                    d += 1;
+                }
+                self.cursor = if self.degrees.get(d) == Some(&x) {
+                    (*x).right
+                } else {
+                    x
+                };
+            }
+        }
+        self.unsettled == 0
+    }
+
+    /// Turns on incremental consolidation: after every push, decrease_key and meld, the heap does
+    /// a [consolidate_step](FibonacciHeap::consolidate_step) with the given budget. A budget of 0
+    /// (the default) turns it off again.
+    ///
+    /// This makes each of these operations take O(`budget`) time instead of O(1), but keeps the
+    /// root list short, so that a pop rarely has many roots to link. A small budget like 4 is
+    /// usually enough to keep up with the pushes. The budget stays with the heap when another heap
+    /// is melded into it.
+    ///
+    /// If a comparison panics during the step of a push, the element has been pushed, but its
+    /// handle is lost.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fibonacci_heap_rust::FibonacciHeap;
+    ///
+    /// let mut heap = FibonacciHeap::new();
+    /// heap.set_consolidation_budget(4);
+    /// for i in (0..1000).rev() {
+    ///     heap.push(i);
+    /// }
+    /// assert_eq!(heap.consolidation_budget(), 4);
+    /// assert_eq!(heap.pop(), Some(0));
+    /// ```
+    pub fn set_consolidation_budget(&mut self, budget: usize) {
+        self.step_budget = budget;
+    }
+
+    /// Returns the budget that was set with
+    /// [set_consolidation_budget](FibonacciHeap::set_consolidation_budget), or 0 if incremental
+    /// consolidation is turned off.
+    pub fn consolidation_budget(&self) -> usize {
+        self.step_budget
+    }
+
+    /// Lets the next step start at `root`, a root that has just become unsettled, unless the
+    /// cursor is at an unsettled root already. Moving it anyway would leave that root behind
+    /// until the next pop, when a step ran out of budget in the middle of linking it.
+    ///
+    /// # Safety
+    ///
+    /// `root` must be a root of this heap.
+    pub(crate) unsafe fn move_cursor(&mut self, root: *mut Node<T>) {
+        let x = self.cursor;
+        if x.is_null() || !(*x).parent.is_null() || self.degrees.get((*x).degree) == Some(&x) {
+            self.cursor = root;
+        }
+    }
+
+    /// Does the step of incremental consolidation after an operation, if it is turned on.
+    pub(crate) fn incremental_step(&mut self) {
+        if self.step_budget > 0 {
+            self.consolidate_step(self.step_budget);
+        }
+    }
+}
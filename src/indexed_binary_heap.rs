@@ -0,0 +1,276 @@
+use crate::{HeapTag, Shared};
+
+/// A binary min-heap in a `Vec`, in which every element knows its position, so that it can be
+/// addressed by an [IndexedPtr] to decrease its key or to delete it.
+///
+/// All operations take O(log n) time in the worst case, and not just amortized, and the elements
+/// are stored compactly, which makes this a good choice for small heaps, and for workloads with
+/// few `decrease_key`s. Melding moves the elements of the other heap over one by one, so it takes
+/// O(m log(n + m)) time for a heap of m elements.
+///
+/// The handles work just like [NodePtr](crate::NodePtr)s: they keep track of whether their element
+/// is still in the heap, and they stay valid when the heap is melded into another one.
+///
+/// If the `Ord` implementation of `T` panics, the heap is left in a consistent state (every
+/// element is still in it exactly once, and every handle still points to its element), but the
+/// elements may not be in heap order anymore, so later pops may return them out of order.
+///
+/// # Examples
+///
+/// ```
+/// use fibonacci_heap_rust::IndexedBinaryHeap;
+///
+/// let mut heap = IndexedBinaryHeap::new();
+/// heap.push(5);
+/// let seven = heap.push(7);
+/// let three = heap.push(3);
+/// heap.decrease_key(&seven, 1);
+/// assert_eq!(heap.delete(three), Some(3));
+/// assert_eq!(heap.pop(), Some(1));
+/// assert_eq!(heap.pop(), Some(5));
+/// assert_eq!(heap.pop(), None);
+/// ```
+pub struct IndexedBinaryHeap<T> {
+    /// The elements, in heap order: every element is at most as large as the elements at
+    /// `2 * i + 1` and `2 * i + 2`.
+    entries: Vec<Entry<T>>,
+    /// The tag that identifies this heap. It is created when the first element is pushed.
+    tag: Option<Shared<HeapTag>>,
+}
+
+/// A handle to an element inside an [IndexedBinaryHeap], which works just like a
+/// [NodePtr](crate::NodePtr).
+pub struct IndexedPtr(Shared<Slot>);
+
+struct Entry<T> {
+    key: T,
+    slot: Shared<Slot>,
+}
+
+/// The position of an element, which is shared between the heap and the handles to it.
+struct Slot {
+    /// The index of the element in `entries`.
+    index: usize,
+    /// Whether the element has been popped or deleted, or the heap has been dropped.
+    invalidated: bool,
+    /// The tag of the heap which this element was pushed into.
+    heap_tag: Shared<HeapTag>,
+}
+
+impl Clone for IndexedPtr {
+    fn clone(&self) -> IndexedPtr {
+        IndexedPtr(self.0.clone())
+    }
+}
+
+impl<T: Ord> IndexedBinaryHeap<T> {
+    /// Constructs a new, empty heap.
+    pub const fn new() -> IndexedBinaryHeap<T> {
+        IndexedBinaryHeap {
+            entries: Vec::new(),
+            tag: None,
+        }
+    }
+
+    /// Checks whether the heap is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the number of elements in the heap.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns a reference to the minimum element of the heap, or `None` if it is empty.
+    pub fn peek(&self) -> Option<&T> {
+        self.entries.first().map(|entry| &entry.key)
+    }
+
+    /// Produces a heap from melding two existing heaps. The [IndexedPtr]s pointing to elements of
+    /// either heap can be used with the new heap.
+    pub fn from_meld(
+        mut heap1: IndexedBinaryHeap<T>,
+        mut heap2: IndexedBinaryHeap<T>,
+    ) -> IndexedBinaryHeap<T> {
+        heap1.absorb(&mut heap2);
+        heap1
+    }
+
+    /// Moves all elements of `other` into this heap, leaving `other` empty.
+    pub(crate) fn absorb(&mut self, other: &mut IndexedBinaryHeap<T>) {
+        if self.entries.len() < other.entries.len() {
+            // move the smaller heap into the larger one
+            std::mem::swap(&mut self.entries, &mut other.entries);
+        }
+        self.tag = HeapTag::union(self.tag.take(), other.tag.take());
+        // first move all elements over, and only then restore the heap order, so that every element
+        // is in `self` at its recorded index even if a comparison panics
+        let start = self.entries.len();
+        for entry in other.entries.drain(..) {
+            entry.slot.borrow_mut().index = self.entries.len();
+            self.entries.push(entry);
+        }
+        for i in start..self.entries.len() {
+            self.sift_up(i);
+        }
+    }
+
+    /// Inserts an element into the heap, and returns a handle to it.
+    pub fn push(&mut self, item: T) -> IndexedPtr {
+        let slot = Shared::new(Slot {
+            index: self.entries.len(),
+            invalidated: false,
+            heap_tag: self.tag.get_or_insert_with(HeapTag::new).clone(),
+        });
+        self.entries.push(Entry {
+            key: item,
+            slot: slot.clone(),
+        });
+        self.sift_up(self.entries.len() - 1);
+        IndexedPtr(slot)
+    }
+
+    /// Decreases the key of the element that `elem` points to. If this element is not in the heap
+    /// anymore, or if `new_key` is larger than its key, nothing happens.
+    ///
+    /// Panics if `elem` points to an element of another heap.
+    pub fn decrease_key(&mut self, elem: &IndexedPtr, new_key: T) {
+        let Some(index) = self.index_of(elem, "decrease_key") else {
+            return;
+        };
+        if new_key > self.entries[index].key {
+            return;
+        }
+        let old_key = std::mem::replace(&mut self.entries[index].key, new_key);
+        self.sift_up(index);
+        drop(old_key);
+    }
+
+    /// Deletes the element that `elem` points to, and returns it. If this element is not in the
+    /// heap anymore, nothing happens and `None` is returned.
+    ///
+    /// Panics if `elem` points to an element of another heap.
+    pub fn delete(&mut self, elem: IndexedPtr) -> Option<T> {
+        let index = self.index_of(&elem, "delete")?;
+        Some(self.remove(index))
+    }
+
+    /// Extracts the minimum element from the heap and returns it.
+    pub fn pop(&mut self) -> Option<T> {
+        if self.entries.is_empty() {
+            None
+        } else {
+            Some(self.remove(0))
+        }
+    }
+
+    /// Removes the element at `index`, and moves the last element into its place.
+    fn remove(&mut self, index: usize) -> T {
+        let removed = self.entries.swap_remove(index);
+        removed.slot.borrow_mut().invalidated = true;
+        if index < self.entries.len() {
+            self.entries[index].slot.borrow_mut().index = index;
+            // the element that took its place may have to move up or down
+            if index > 0 && self.entries[index].key < self.entries[(index - 1) / 2].key {
+                self.sift_up(index);
+            } else {
+                self.sift_down(index);
+            }
+        }
+        removed.key
+    }
+
+    /// Moves the element at `index` up until its parent is not larger than it.
+    fn sift_up(&mut self, mut index: usize) {
+        while index > 0 {
+            let parent = (index - 1) / 2;
+            if self.entries[index].key >= self.entries[parent].key {
+                break;
+            }
+            self.swap(index, parent);
+            index = parent;
+        }
+    }
+
+    /// Moves the element at `index` down until its children are not smaller than it.
+    fn sift_down(&mut self, mut index: usize) {
+        loop {
+            let left = 2 * index + 1;
+            let right = left + 1;
+            let mut smallest = index;
+            if left < self.entries.len() && self.entries[left].key < self.entries[smallest].key {
+                smallest = left;
+            }
+            if right < self.entries.len() && self.entries[right].key < self.entries[smallest].key {
+                smallest = right;
+            }
+            if smallest == index {
+                break;
+            }
+            self.swap(index, smallest);
+            index = smallest;
+        }
+    }
+
+    /// Swaps the elements at `i` and `j`, and updates their positions.
+    fn swap(&mut self, i: usize, j: usize) {
+        self.entries.swap(i, j);
+        self.entries[i].slot.borrow_mut().index = i;
+        self.entries[j].slot.borrow_mut().index = j;
+    }
+
+    /// Returns the index of the element that `elem` points to, or `None` if that element is not in
+    /// any heap anymore. Panics if the element is in some other heap than this one.
+    fn index_of(&self, elem: &IndexedPtr, method: &str) -> Option<usize> {
+        let slot = elem.0.borrow();
+        if slot.invalidated {
+            return None;
+        }
+        if !HeapTag::belongs_to(&slot.heap_tag, self.tag.as_ref()) {
+            panic!(
+                "Oh no... you called some_heap.{method}(..) on \
+                an element that was never inserted into this heap!"
+            )
+        }
+        Some(slot.index)
+    }
+}
+
+#[cfg(test)]
+impl<T: Ord> IndexedBinaryHeap<T> {
+    /// Panics if the elements are not in heap order, or if some position is wrong.
+    pub(crate) fn check_invariants(&self) {
+        self.check_structure();
+        for i in 1..self.entries.len() {
+            assert!(
+                self.entries[i].key >= self.entries[(i - 1) / 2].key,
+                "heap order violated"
+            );
+        }
+    }
+
+    /// Panics if some position is wrong.
+    pub(crate) fn check_structure(&self) {
+        for (i, entry) in self.entries.iter().enumerate() {
+            let slot = entry.slot.borrow();
+            assert!(slot.index == i && !slot.invalidated, "wrong position");
+        }
+    }
+}
+
+impl<T> Drop for IndexedBinaryHeap<T> {
+    fn drop(&mut self) {
+        // invalidate all handles before dropping any key, in case dropping a key panics
+        for entry in &self.entries {
+            entry.slot.borrow_mut().invalidated = true;
+        }
+    }
+}
+
+impl<T: Ord> Default for IndexedBinaryHeap<T> {
+    /// Creates an empty `IndexedBinaryHeap<T>`.
+    fn default() -> IndexedBinaryHeap<T> {
+        IndexedBinaryHeap::new()
+    }
+}
@@ -1,12 +1,76 @@
-use std::{cell::RefCell, rc::Rc};
+pub mod algorithms;
+mod binomial_heap;
+mod bulk;
+#[cfg(feature = "sync")]
+mod concurrent;
+mod event_queue;
+mod incremental;
+mod indexed_binary_heap;
+mod merge;
+mod pairing_heap;
+mod priority_queue;
+mod select;
+#[cfg(feature = "serde")]
+mod serde_impls;
+mod shared;
+mod snapshot;
+mod stats;
+mod timer_queue;
+mod trace;
+
+pub use binomial_heap::{BinomialHeap, BinomialPtr};
+#[cfg(feature = "sync")]
+pub use concurrent::ConcurrentFibonacciHeap;
+pub use event_queue::{EventHandle, EventQueue};
+pub use indexed_binary_heap::{IndexedBinaryHeap, IndexedPtr};
+pub use merge::{merge_sorted, MergeSorted};
+pub use pairing_heap::{PairingHeap, PairingPtr};
+pub use priority_queue::AddressablePriorityQueue;
+pub use select::smallest_k;
+use shared::Shared;
+pub use snapshot::{SnapshotError, SnapshotKey};
+#[cfg(feature = "stats")]
+pub use stats::HeapStats;
+use stats::Recorder;
+pub use timer_queue::{TimerHandle, TimerQueue};
+pub use trace::{HeapWithHandlesById, RecordedPtr, RecordingHeap, ReplayError, Trace, TraceOp};
 
 /// This is a min-Fibonacci heap.
+///
+/// With the `sync` feature enabled, the heap is `Send` (if `T: Send`) and `Sync` (if `T: Sync`),
+/// and so are the [NodePtr]s pointing into it.
+///
+/// If the `Ord` or `Drop` implementation of `T` panics in the middle of an operation, the heap is
+/// left in a consistent state: it can still be used and dropped, and every element is dropped
+/// exactly once. The only thing that may be off is the next element that is popped, which is not
+/// necessarily the minimum if a comparison panicked while popping or melding.
 pub struct FibonacciHeap<T> {
     /// The current number of nodes in the Fibonacci heap.
     n: usize,
     /// A pointer to the current minimal node in the Fibonacci heap. This is a null pointer if the
     /// heap is empty.
     min: *mut Node<T>,
+    /// Roots with distinct degrees: `degrees[d]` is a root of degree `d`, or a null pointer. Every
+    /// root is either in here ("settled"), or not yet ("unsettled"), and consolidating means
+    /// linking unsettled roots with settled roots of the same degree until none are left. Both
+    /// `consolidate` and [consolidate_step](FibonacciHeap::consolidate_step) keep this up to date,
+    /// so that the latter can pick up where it left off, and the buffer is reused across pops.
+    degrees: Vec<*mut Node<T>>,
+    /// The number of roots in `degrees`.
+    settled: usize,
+    /// The number of roots that are not in `degrees`.
+    unsettled: usize,
+    /// The root at which the next `consolidate_step` continues, or a null pointer. This may point
+    /// to a node that is not a root anymore, but never to a node that has been freed.
+    cursor: *mut Node<T>,
+    /// The budget of the `consolidate_step` that is done after every push, decrease_key and meld,
+    /// or 0 if the heap is only consolidated by pops.
+    step_budget: usize,
+    /// The tag that identifies this heap. It is created when the first element is pushed, so it is
+    /// `None` for heaps that have never contained any elements.
+    tag: Option<Shared<HeapTag>>,
+    /// The statistics of the heap, which are only kept with the `stats` feature enabled.
+    stats: Recorder,
 }
 
 /// A smart pointer that points to an element inside the Fibonacci heap.
@@ -44,8 +108,11 @@ pub struct FibonacciHeap<T> {
 /// assert_eq!(heap.pop(), Some(137));
 /// assert_eq!(heap.pop(), None);
 /// ```
-#[derive(Clone)]
-pub struct NodePtr<T>(Rc<RefCell<NodePtrInternal<T>>>);
+pub struct NodePtr<T>(Shared<NodePtrInternal<T>>);
+
+/// A heap along with handles to its elements, as returned by the functions that restore a heap
+/// from a snapshot, such as [read_snapshot_with_handles](FibonacciHeap::read_snapshot_with_handles).
+pub type HeapWithHandles<T> = (FibonacciHeap<T>, Vec<NodePtr<T>>);
 
 /// A custom smart pointer to point to an element inside the Fibonacci heap from outside.
 struct NodePtrInternal<T> {
@@ -56,8 +123,23 @@ struct NodePtrInternal<T> {
     invalidated: bool,
     /// A raw pointer to the [Node].
     ptr: *mut Node<T>,
-    /// A raw pointer to the [FibonacciHeap] which this node is/was in.
-    heap_ptr: *const FibonacciHeap<T>,
+    /// The tag of the [FibonacciHeap] which this node was pushed into.
+    heap_tag: Shared<HeapTag>,
+}
+
+/// Identifies a [FibonacciHeap] (or one of the other heaps in this crate), so that we can check
+/// whether a [NodePtr] belongs to the heap it is used with. We cannot use the address of the heap
+/// for this, because heaps can be moved.
+///
+/// When two heaps are melded, the tag of one of them is made to point to the tag of the other one
+/// (just like in a union-find data structure), so that the handles of both heaps are recognized by
+/// the melded heap.
+struct HeapTag {
+    /// The tag of the heap that the heap with this tag has been melded into, if any.
+    melded_into: Option<Shared<HeapTag>>,
+    /// An upper bound on the length of the chain of `melded_into` tags that ends at this tag. We
+    /// always make the tag with the lower rank point to the other one, which keeps chains short.
+    rank: u32,
 }
 
 /// A node in the Fibonacci heap, containing the key, some pointers to other nodes and some
@@ -94,7 +176,13 @@ struct Node<T> {
     /// when the node is popped, we need to invalidate that smart pointer. But in order to
     /// invalidate the smart pointer, we need to find it first! So, this struct field is a smart pointer (Rc) to
     /// the smart pointer which points to this node.
-    outside_ref: Rc<RefCell<NodePtrInternal<T>>>,
+    outside_ref: Shared<NodePtrInternal<T>>,
+}
+
+impl<T> Clone for NodePtr<T> {
+    fn clone(&self) -> NodePtr<T> {
+        NodePtr(self.0.clone())
+    }
 }
 
 impl<T> NodePtr<T> {
@@ -122,6 +210,13 @@ impl<T: Ord> FibonacciHeap<T> {
         FibonacciHeap {
             n: 0,
             min: std::ptr::null_mut(),
+            degrees: Vec::new(),
+            settled: 0,
+            unsettled: 0,
+            cursor: std::ptr::null_mut(),
+            step_budget: 0,
+            tag: None,
+            stats: Recorder::new(),
         }
     }
 
@@ -202,26 +297,48 @@ impl<T: Ord> FibonacciHeap<T> {
     /// assert_eq!(heap.pop(), Some("World".to_string()));
     /// assert_eq!(heap.pop(), None);
     /// ```
-    pub fn from_meld(heap1: FibonacciHeap<T>, heap2: FibonacciHeap<T>) -> FibonacciHeap<T> {
-        let mut heap = FibonacciHeap::<T>::new();
-        if heap1.is_empty() {
-            return heap2;
-        } else if heap2.is_empty() {
-            return heap1;
+    pub fn from_meld(mut heap1: FibonacciHeap<T>, mut heap2: FibonacciHeap<T>) -> FibonacciHeap<T> {
+        heap1.absorb(&mut heap2);
+        heap1
+    }
+
+    /// Moves all elements of `other` into this heap in O(1) time, leaving `other` empty. The
+    /// [NodePtr]s pointing to elements of `other` can be used with this heap afterwards.
+    ///
+    /// If the comparison of the two minimums panics, neither heap is changed.
+    pub(crate) fn absorb(&mut self, other: &mut FibonacciHeap<T>) {
+        if other.is_empty() {
+            return;
+        } else if self.is_empty() {
+            std::mem::swap(self, other);
+            // the counters and the settings stay with the heap that did the work
+            std::mem::swap(&mut self.stats, &mut other.stats);
+            self.stats.absorb(&mut other.stats);
+            std::mem::swap(&mut self.step_budget, &mut other.step_budget);
+            return;
         }
         unsafe {
-            FibonacciHeap::concatenate_circular_lists(heap1.min, heap2.min);
-
-            heap.min = if (*heap1.min).key < (*heap2.min).key {
-                heap1.min
-            } else {
-                heap2.min
-            };
+            // compare before relinking anything, in case the comparison panics
+            let other_has_min = (*other.min).key < (*self.min).key;
+            FibonacciHeap::concatenate_circular_lists(self.min, other.min);
+            if other_has_min {
+                self.min = other.min;
+            }
         }
-        heap.n = heap1.n + heap2.n;
-        std::mem::forget(heap1);
-        std::mem::forget(heap2);
-        heap
+        self.n += other.n;
+        self.stats.absorb(&mut other.stats);
+        self.tag = HeapTag::union(self.tag.take(), other.tag.take());
+        // the roots of `other` are not in our `degrees`, so they are all unsettled now
+        self.unsettled += other.settled + other.unsettled;
+        unsafe { self.move_cursor(other.min) };
+        // the nodes now belong to `self`, so `other` must not free them when it is dropped
+        other.min = std::ptr::null_mut();
+        other.n = 0;
+        other.degrees.clear();
+        other.settled = 0;
+        other.unsettled = 0;
+        other.cursor = std::ptr::null_mut();
+        self.incremental_step();
     }
 
     /// Insert an element into the Fibonacci heap.
@@ -248,23 +365,19 @@ impl<T: Ord> FibonacciHeap<T> {
     /// assert!(heap.len() == 3);
     /// ```
     pub fn push(&mut self, item: T) -> NodePtr<T> {
-        let node: *mut Node<T> = Box::into_raw(Box::new(Node {
-            key: item,
-            left: std::ptr::null_mut(),
-            right: std::ptr::null_mut(),
-            parent: std::ptr::null_mut(),
-            child: std::ptr::null_mut(),
-            degree: 0,
-            mark: false,
-            outside_ref: Rc::new(RefCell::new(NodePtrInternal {
-                ptr: std::ptr::null_mut(),
-                heap_ptr: std::ptr::addr_of!(*self),
-                invalidated: false,
-            })),
-        }));
+        let node = self.add_root(item);
+        let handle = NodePtr(unsafe { (*node).outside_ref.clone() });
+        self.incremental_step();
+        handle
+    }
+
+    /// Adds a new root for `item`, like `push` but without making a handle or consolidating.
+    fn add_root(&mut self, item: T) -> *mut Node<T> {
+        // compare before allocating anything, in case the comparison panics
+        let is_new_min = self.min.is_null() || unsafe { item < (*self.min).key };
+        let node = self.new_node(item);
         unsafe {
             // my first `unsafe` ever! :) 20 april 2024
-            (*node).outside_ref.borrow_mut().ptr = node;
             if self.min.is_null() {
                 (*node).left = node;
                 (*node).right = node;
@@ -272,13 +385,16 @@ impl<T: Ord> FibonacciHeap<T> {
             } else {
                 FibonacciHeap::add_node_to_nonempty_circular_list(node, self.min);
 
-                if (*node).key < (*self.min).key {
+                if is_new_min {
                     self.min = node;
                 }
             }
             self.n += 1;
-            NodePtr((*node).outside_ref.clone())
+            self.unsettled += 1;
+            self.stats.add(true, 0, false);
+            self.move_cursor(node);
         }
+        node
     }
 
     /// Decreases the key of the element pointed to by `elem` from the Fibonacci heap it is in. If this element
@@ -288,54 +404,31 @@ impl<T: Ord> FibonacciHeap<T> {
     /// If you call this function on some Fibonacci heap `H` and element `x`, in such a way that `x` is/was never an element of
     /// `H` but of some other Fibonacci heap instead, the method panics.
     pub fn decrease_key(&mut self, elem: &NodePtr<T>, new_key: T) {
-        unsafe fn cut<T: Ord>(
-            heap: &mut FibonacciHeap<T>,
-            node: *mut Node<T>,
-            parent: *mut Node<T>,
-        ) {
-            (*parent).degree -= 1;
-            FibonacciHeap::remove_from_circular_list(node);
-            FibonacciHeap::add_node_to_nonempty_circular_list(node, heap.min);
-            (*node).parent = std::ptr::null_mut();
-            (*node).mark = false;
-        }
-
-        unsafe fn cascading_cut<T: Ord>(heap: &mut FibonacciHeap<T>, node: *mut Node<T>) {
-            let parent = (*node).parent;
-            if !parent.is_null() {
-                if !(*node).mark {
-                    (*node).mark = true;
-                } else {
-                    cut(heap, node, parent);
-                    cascading_cut(heap, parent);
-                }
-            }
-        }
-
-        if elem.0.borrow().invalidated {
+        let Some(node) = self.node_of(elem, "decrease_key") else {
             return;
-        }
-        if &new_key > unsafe { &(*elem.0.borrow().ptr).key } {
-            // can only decrease key, not increase
-            return;
-        }
-        if !std::ptr::eq(elem.0.borrow().heap_ptr, std::ptr::addr_of!(*self)) {
-            panic!(
-                "Oh no... you called some_heap.decrease_key(..) on \
-                an element that was never inserted into this heap!"
-            )
-        }
+        };
         unsafe {
-            let node: *mut Node<T> = elem.0.borrow().ptr;
-            (*node).key = new_key;
+            if new_key > (*node).key {
+                // can only decrease key, not increase
+                return;
+            }
+            // Do all comparisons before changing anything, in case one of them panics. For the
+            // same reason, the old key is only dropped once the heap is consistent again.
             let parent = (*node).parent;
-            if !parent.is_null() && (*node).key < (*parent).key {
-                cut(self, node, parent);
-                cascading_cut(self, parent);
+            let violates_heap_order = !parent.is_null() && new_key < (*parent).key;
+            let is_new_min = new_key < (*self.min).key;
+            let old_key = std::mem::replace(&mut (*node).key, new_key);
+            if violates_heap_order {
+                self.cut(node, parent, false);
+                self.cascading_cut(parent);
+                // the roots that were just cut are next to `min`, starting at its right neighbor
+                self.move_cursor((*self.min).right);
             }
-            if (*node).key < (*self.min).key {
+            if is_new_min {
                 (self.min) = node;
             }
+            drop(old_key);
+            self.incremental_step();
         }
     }
 
@@ -346,45 +439,12 @@ impl<T: Ord> FibonacciHeap<T> {
     /// If you call this function on some heap `H` and element `x`, in such a way that `x` is/was never an element of
     /// `H` but of some other heap instead, the method panics.
     pub fn delete(&mut self, elem: NodePtr<T>) -> Option<T> {
-        unsafe fn cut<T: Ord>(
-            heap: &mut FibonacciHeap<T>,
-            node: *mut Node<T>,
-            parent: *mut Node<T>,
-        ) {
-            (*parent).degree -= 1;
-            FibonacciHeap::remove_from_circular_list(node);
-            FibonacciHeap::add_node_to_nonempty_circular_list(node, heap.min);
-            (*node).parent = std::ptr::null_mut();
-            (*node).mark = false;
-        }
-
-        unsafe fn cascading_cut<T: Ord>(heap: &mut FibonacciHeap<T>, node: *mut Node<T>) {
-            let parent = (*node).parent;
-            if !parent.is_null() {
-                if !(*node).mark {
-                    (*node).mark = true;
-                } else {
-                    cut(heap, node, parent);
-                    cascading_cut(heap, parent);
-                }
-            }
-        }
-
-        if elem.0.borrow().invalidated {
-            return None;
-        }
-        if !std::ptr::eq(elem.0.borrow().heap_ptr, std::ptr::addr_of!(*self)) {
-            panic!(
-                "Oh no... you called some_heap.decrease_key(..) on \
-                an element that was never inserted into this heap!"
-            )
-        }
-        let node: *mut Node<T> = elem.0.borrow().ptr;
+        let node = self.node_of(&elem, "delete")?;
         unsafe {
             let parent = (*node).parent;
             if !parent.is_null() {
-                cut(self, node, parent);
-                cascading_cut(self, parent);
+                self.cut(node, parent, false);
+                self.cascading_cut(parent);
             }
         }
         self.min = node;
@@ -397,6 +457,12 @@ impl<T: Ord> FibonacciHeap<T> {
         let popped = self.min;
         if !popped.is_null() {
             unsafe {
+                // `degrees` is rebuilt from scratch by `consolidate`, and must not keep pointing to
+                // the popped node
+                self.unsettled = self.settled + self.unsettled + (*popped).degree - 1;
+                self.settled = 0;
+                self.degrees.clear();
+                self.cursor = std::ptr::null_mut();
                 (*popped).outside_ref.borrow_mut().invalidated = true;
                 let mut child = (*popped).child;
                 if !child.is_null() {
@@ -406,30 +472,148 @@ impl<T: Ord> FibonacciHeap<T> {
                     }
                 }
                 FibonacciHeap::concatenate_circular_lists(child, popped);
-                if (*popped).right != popped {
+                self.stats.pop((*popped).degree, (*popped).mark);
+                let popped = if (*popped).right != popped {
                     FibonacciHeap::remove_from_circular_list(popped);
                     self.min = (*popped).right;
+                    // The popped node is not in the heap anymore, so take ownership of it before
+                    // consolidating: if a comparison panics, it is then freed while unwinding.
+                    let popped = Box::from_raw(popped);
+                    self.n -= 1;
+                    self.stats.consolidate();
                     self.consolidate();
+                    popped
                 } else {
                     self.min = std::ptr::null_mut();
-                }
+                    self.n -= 1;
+                    Box::from_raw(popped)
+                };
+                Some(popped.key)
             }
-            self.n -= 1;
-            unsafe { Some(Box::from_raw(popped).key) }
         } else {
             None
         }
     }
 
+    /// Allocates a new node for `key` that is not linked to any other node yet (all of its pointers
+    /// are null), and that belongs to this heap.
+    fn new_node(&mut self, key: T) -> *mut Node<T> {
+        let node: *mut Node<T> = Box::into_raw(Box::new(Node {
+            key,
+            left: std::ptr::null_mut(),
+            right: std::ptr::null_mut(),
+            parent: std::ptr::null_mut(),
+            child: std::ptr::null_mut(),
+            degree: 0,
+            mark: false,
+            outside_ref: Shared::new(NodePtrInternal {
+                ptr: std::ptr::null_mut(),
+                heap_tag: self.tag.get_or_insert_with(HeapTag::new).clone(),
+                invalidated: false,
+            }),
+        }));
+        unsafe { (*node).outside_ref.borrow_mut().ptr = node };
+        node
+    }
+
+    /// Returns a reference to the minimum element of the Fibonacci heap, or `None` if it is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fibonacci_heap_rust::FibonacciHeap;
+    ///
+    /// let mut heap = FibonacciHeap::from([3, 1, 2]);
+    /// assert_eq!(heap.peek(), Some(&1));
+    /// heap.pop();
+    /// assert_eq!(heap.peek(), Some(&2));
+    /// ```
+    pub fn peek(&self) -> Option<&T> {
+        if self.min.is_null() {
+            None
+        } else {
+            unsafe { Some(&(*self.min).key) }
+        }
+    }
+
+    /// Returns a pointer to the node that `elem` points to, or `None` if that node is not in any
+    /// heap anymore.
+    ///
+    /// Panics if the node is in some other heap than this one; `method` is only used for the panic
+    /// message. Note that we must not touch the node itself before we know that it is in this heap,
+    /// since the other heap might be in use by another thread.
+    fn node_of(&self, elem: &NodePtr<T>, method: &str) -> Option<*mut Node<T>> {
+        let (node, heap_tag) = {
+            let elem = elem.0.borrow();
+            if elem.invalidated {
+                return None;
+            }
+            (elem.ptr, elem.heap_tag.clone())
+        };
+        if !HeapTag::belongs_to(&heap_tag, self.tag.as_ref()) {
+            panic!(
+                "Oh no... you called some_heap.{method}(..) on \
+                an element that was never inserted into this heap!"
+            )
+        }
+        Some(node)
+    }
+
+    /// Removes `node` from the child list of its parent `parent`, and adds it to the root list.
+    /// `cascading` tells whether this is a cascading cut, which is only used for the statistics.
+    unsafe fn cut(&mut self, node: *mut Node<T>, parent: *mut Node<T>, cascading: bool) {
+        self.stats.cut((*node).mark, cascading);
+        if self.degrees.get((*parent).degree) == Some(&parent) {
+            // the degree of a settled root is about to change, so it is not settled anymore
+            self.degrees[(*parent).degree] = std::ptr::null_mut();
+            self.settled -= 1;
+            self.unsettled += 1;
+        }
+        self.unsettled += 1;
+        (*parent).degree -= 1;
+        if std::ptr::eq((*parent).child, node) {
+            // make sure that the parent does not keep pointing to the node we are cutting
+            (*parent).child = if (*parent).degree == 0 {
+                std::ptr::null_mut()
+            } else {
+                (*node).right
+            };
+        }
+        FibonacciHeap::remove_from_circular_list(node);
+        FibonacciHeap::add_node_to_nonempty_circular_list(node, self.min);
+        (*node).parent = std::ptr::null_mut();
+        (*node).mark = false;
+    }
+
+    /// Called on a node that has just lost a child. If the node was already marked (so it has now
+    /// lost two children), it is cut from its parent as well, which in turn has lost a child, and
+    /// so on. This is a loop rather than recursion, because the chain of marked ancestors can be
+    /// arbitrarily long.
+    unsafe fn cascading_cut(&mut self, mut node: *mut Node<T>) {
+        loop {
+            let parent = (*node).parent;
+            if parent.is_null() {
+                return;
+            }
+            if !(*node).mark {
+                (*node).mark = true;
+                self.stats.mark();
+                return;
+            }
+            self.cut(node, parent, true);
+            node = parent;
+        }
+    }
+
     /// This method basically fixes up the Fibonacci heap (it is called by the `pop()` method) such
     /// that every root in the root list has a unique degree. This reduces the number of trees and
     /// that is good.
+    ///
+    /// `degrees` must be empty, so that all roots are unsettled.
     unsafe fn consolidate(&mut self) {
-        // if arr[i] = some node, then that node is a root with degree i
-        let mut arr: Vec<*mut Node<T>> = vec![
-                std::ptr::null_mut();
-                (self.n as f64).log((1.0 + (5f64).sqrt()) / 2.0).floor() as usize + 1 // :-)
-            ];
+        // if degrees[i] = some node, then that node is a root with degree i
+        self.degrees
+            .resize(Self::max_degree(self.n) + 1, std::ptr::null_mut());
 
         // Make sure that each node in the root list has a unique degree
         let last = (*self.min).left;
@@ -443,8 +627,8 @@ impl<T: Ord> FibonacciHeap<T> {
                 finished = true;
             }
             let mut d = (*x).degree;
-            while !arr[d].is_null() {
-                let mut y = arr[d];
+            while !self.degrees[d].is_null() {
+                let mut y = self.degrees[d];
                 if (*x).key > (*y).key {
                     std::mem::swap(&mut x, &mut y);
                 }
@@ -456,28 +640,19 @@ impl<T: Ord> FibonacciHeap<T> {
                     // that
                     node_it = (*node_it).left;
                 }
-                FibonacciHeap::remove_from_circular_list(y);
-                (*x).degree += 1;
-                if !(*x).child.is_null() {
-                    Self::add_node_to_nonempty_circular_list(y, (*x).child);
-                } else {
-                    (*y).left = y;
-                    (*y).right = y;
-                    (*x).child = y;
-                }
-                (*y).mark = false;
-                (*y).parent = x;
-
-                arr[d] = std::ptr::null_mut();
+                self.degrees[d] = std::ptr::null_mut();
+                self.settled -= 1;
+                self.link(x, y);
                 d += 1;
             }
-            arr[d] = x;
+            self.degrees[d] = x;
+            self.settled += 1;
+            self.unsettled -= 1;
         }
 
-        self.min = std::ptr::null_mut();
         // root list is intact, but we need to find out who is the new `min`
         let mut min: *mut Node<T> = std::ptr::null_mut();
-        for node in arr {
+        for &node in &self.degrees {
             if !node.is_null() && (min.is_null() || (*node).key < (*min).key) {
                 min = node;
             }
@@ -485,6 +660,37 @@ impl<T: Ord> FibonacciHeap<T> {
         self.min = min;
     }
 
+    /// Makes the root `child` a child of the root `parent`, which must have the same degree. Neither
+    /// of them may be in `degrees`.
+    unsafe fn link(&mut self, parent: *mut Node<T>, child: *mut Node<T>) {
+        FibonacciHeap::remove_from_circular_list(child);
+        if self.min == child {
+            // `self.min` must point to a root at all times, in case a comparison panics
+            self.min = parent;
+        }
+        (*parent).degree += 1;
+        if !(*parent).child.is_null() {
+            Self::add_node_to_nonempty_circular_list(child, (*parent).child);
+        } else {
+            (*child).left = child;
+            (*child).right = child;
+            (*parent).child = child;
+        }
+        self.stats.link((*child).mark, (*parent).degree);
+        (*child).mark = false;
+        (*child).parent = parent;
+    }
+
+    /// Returns an upper bound on the degree of any node in a Fibonacci heap with `n` nodes.
+    ///
+    /// The maximum degree is at most `log_φ(n)`, where `φ` is the golden ratio. Since
+    /// `log_φ(n) = log₂(n) / log₂(φ) ≈ 1.44 · log₂(n)`, we can bound it using only integer
+    /// arithmetic by `1.5` times the number of bits of `n`, which leaves some margin to spare.
+    const fn max_degree(n: usize) -> usize {
+        let bits = (usize::BITS - n.leading_zeros()) as usize;
+        bits + bits / 2 + 1
+    }
+
     /// Adds a node to a circular doubly linked list. Both inputs must not be null pointers.
     unsafe fn add_node_to_nonempty_circular_list(new_item: *mut Node<T>, list: *mut Node<T>) {
         (*new_item).right = (*list).right;
@@ -514,49 +720,364 @@ impl<T: Ord> FibonacciHeap<T> {
     }
 }
 
+/// Converting a heap from and to a flat description of its forest, which is used to serialize the
+/// exact shape of a heap.
+///
+/// The forest is described by its nodes in preorder: the roots from left to right, starting at the
+/// minimum, and every node directly followed by its children (from left to right, starting at
+/// `child`) and their subtrees. Every node is described by its key, its degree and its mark bit.
+impl<T: Ord> FibonacciHeap<T> {
+    /// Calls `f(key, degree, mark)` for every node, in preorder.
+    pub(crate) fn for_each_in_preorder(&self, mut f: impl FnMut(&T, usize, bool)) {
+        if self.min.is_null() {
+            return;
+        }
+        // the nodes that still have to be visited, in reverse order
+        let mut stack = vec![];
+        unsafe {
+            Self::push_list_reversed(&mut stack, self.min);
+            while let Some(node) = stack.pop() {
+                f(&(*node).key, (*node).degree, (*node).mark);
+                if !(*node).child.is_null() {
+                    Self::push_list_reversed(&mut stack, (*node).child);
+                }
+            }
+        }
+    }
+
+    /// Pushes the nodes of the circular list starting at `first` onto `stack`, last node first.
+    unsafe fn push_list_reversed(stack: &mut Vec<*const Node<T>>, first: *const Node<T>) {
+        let mut node = (*first).left as *const Node<T>;
+        loop {
+            stack.push(node);
+            if std::ptr::eq(node, first) {
+                break;
+            }
+            node = (*node).left;
+        }
+    }
+
+    /// Builds a heap from its nodes in preorder (see [for_each_in_preorder]), and returns it along
+    /// with handles to all nodes in the same order.
+    ///
+    /// The description is checked thoroughly, so that the result is always a valid Fibonacci heap:
+    /// the degrees must match the number of children, the keys must be in heap order, and the
+    /// children of every node must be in a state that linking and cutting can lead to. That is,
+    /// they can be put in an order (the order in which they were linked) in which the `i`th child
+    /// has degree at least `i - 1`, or `i - 2` if it is marked, since it lost a child after being
+    /// linked. Every operation keeps it that way, so a node of degree `d` always has at least
+    /// `F(d + 2)` nodes in its tree, where `F` are the Fibonacci numbers, which is what bounds the
+    /// degrees in `consolidate`. Otherwise, an error message is returned.
+    ///
+    /// [for_each_in_preorder]: FibonacciHeap::for_each_in_preorder
+    pub(crate) fn from_preorder(
+        nodes: impl IntoIterator<Item = (T, usize, bool)>,
+    ) -> Result<HeapWithHandles<T>, &'static str> {
+        /// A node whose subtree has not been read completely yet.
+        struct Open<T> {
+            node: *mut Node<T>,
+            /// The number of children that are still to come.
+            missing_children: usize,
+            /// Where the ranks of the children of the node start in `ranks`.
+            ranks_start: usize,
+        }
+
+        let mut heap = FibonacciHeap::new();
+        let mut handles = vec![];
+        let mut open: Vec<Open<T>> = vec![];
+        // For the children of the open nodes that have been read completely, the latest position
+        // at which they may have been linked (`degree + 1`, or `degree + 2` if marked).
+        let mut ranks: Vec<usize> = vec![];
+        // The minimal root. `heap.min` stays at the first root until the end, so that the roots
+        // can be appended to the end of the root list in order.
+        let mut min: *mut Node<T> = std::ptr::null_mut();
+        // Every node is linked into the heap right away, so that the heap can be dropped at any
+        // point if the description turns out to be invalid.
+        for (key, degree, mark) in nodes {
+            unsafe {
+                let parent = open.last().map(|open| open.node);
+                // compare before allocating or linking anything, in case the comparison panics
+                let is_new_min = match parent {
+                    Some(parent) if key < (*parent).key => return Err("heap order violated"),
+                    Some(_) => false,
+                    None => min.is_null() || key < (*min).key,
+                };
+                let node = heap.new_node(key);
+                (*node).mark = mark;
+                match parent {
+                    Some(parent) => {
+                        (*node).parent = parent;
+                        let first = (*parent).child;
+                        if first.is_null() {
+                            (*node).left = node;
+                            (*node).right = node;
+                            (*parent).child = node;
+                        } else {
+                            FibonacciHeap::add_node_to_nonempty_circular_list(node, (*first).left);
+                        }
+                        (*parent).degree += 1;
+                    }
+                    None if heap.min.is_null() => {
+                        (*node).left = node;
+                        (*node).right = node;
+                        heap.min = node;
+                    }
+                    None => {
+                        FibonacciHeap::add_node_to_nonempty_circular_list(node, (*heap.min).left);
+                    }
+                }
+                if is_new_min {
+                    min = node;
+                }
+                heap.n += 1;
+                heap.unsettled += parent.is_none() as usize;
+                heap.stats.add(parent.is_none(), degree, mark);
+                handles.push(NodePtr((*node).outside_ref.clone()));
+                if let Some(parent) = open.last_mut() {
+                    parent.missing_children -= 1;
+                }
+                open.push(Open {
+                    node,
+                    missing_children: degree,
+                    ranks_start: ranks.len(),
+                });
+            }
+            // close all subtrees that are complete now
+            while let Some(last) = open.last() {
+                if last.missing_children > 0 {
+                    break;
+                }
+                let closed = open.pop().unwrap();
+                // the children can be put in the order of their ranks
+                let children = &mut ranks[closed.ranks_start..];
+                children.sort_unstable();
+                if children.iter().enumerate().any(|(i, &rank)| rank <= i) {
+                    return Err("children of too small degree");
+                }
+                ranks.truncate(closed.ranks_start);
+                if !open.is_empty() {
+                    unsafe { ranks.push((*closed.node).degree + 1 + (*closed.node).mark as usize) };
+                }
+            }
+        }
+        if !open.is_empty() {
+            return Err("missing children");
+        }
+        heap.min = min;
+        Ok((heap, handles))
+    }
+}
+
+#[cfg(any(test, fuzzing))]
+impl<T: Ord> FibonacciHeap<T> {
+    /// Panics if the heap is not a valid Fibonacci heap. This is only meant for the tests and the
+    /// fuzz targets (which are built with `--cfg fuzzing`).
+    #[doc(hidden)]
+    pub fn check_invariants(&self) {
+        self.check_structure();
+        if !self.min.is_null() {
+            unsafe {
+                let mut root = (*self.min).right;
+                while root != self.min {
+                    assert!((*root).key >= (*self.min).key, "`min` is not minimal");
+                    root = (*root).right;
+                }
+            }
+        }
+    }
+
+    /// Panics if the heap is not a valid Fibonacci heap, except that `min` does not have to point
+    /// to the minimal root (which is allowed after a comparison panicked).
+    pub(crate) fn check_structure(&self) {
+        if self.min.is_null() {
+            assert_eq!(self.n, 0);
+            assert_eq!((self.settled, self.unsettled), (0, 0));
+            return;
+        }
+        let (mut count, mut roots) = (0, 0);
+        #[cfg(feature = "stats")]
+        let mut marked = 0;
+        // the circular lists that still have to be checked, along with their parents
+        let mut lists = vec![(self.min, std::ptr::null_mut::<Node<T>>())];
+        while let Some((list, parent)) = lists.pop() {
+            let mut len = 0;
+            // the ranks of the children, see `from_preorder`
+            let mut ranks = vec![];
+            let mut elem = list;
+            unsafe {
+                loop {
+                    assert!(std::ptr::eq((*(*elem).right).left, elem), "broken list");
+                    assert!(std::ptr::eq((*elem).parent, parent), "wrong parent");
+                    if !parent.is_null() {
+                        assert!((*elem).key >= (*parent).key, "heap order violated");
+                    }
+                    if (*elem).child.is_null() {
+                        assert_eq!((*elem).degree, 0);
+                    } else {
+                        lists.push(((*elem).child, elem));
+                    }
+                    let outside_ref = (*elem).outside_ref.borrow();
+                    assert!(std::ptr::eq(outside_ref.ptr, elem) && !outside_ref.invalidated);
+                    roots += parent.is_null() as usize;
+                    #[cfg(feature = "stats")]
+                    {
+                        marked += (*elem).mark as usize;
+                    }
+                    count += 1;
+                    len += 1;
+                    ranks.push((*elem).degree + 1 + (*elem).mark as usize);
+                    assert!(count <= self.n, "more nodes than `n`");
+                    elem = (*elem).right;
+                    if std::ptr::eq(elem, list) {
+                        break;
+                    }
+                }
+                if !parent.is_null() {
+                    assert_eq!((*parent).degree, len);
+                    ranks.sort_unstable();
+                    let too_small = ranks.iter().enumerate().any(|(i, &rank)| rank <= i);
+                    assert!(!too_small, "children of too small degree");
+                }
+            }
+        }
+        assert_eq!(count, self.n);
+        let mut settled = 0;
+        for (degree, &root) in self.degrees.iter().enumerate() {
+            if !root.is_null() {
+                unsafe { assert!((*root).parent.is_null() && (*root).degree == degree) };
+                settled += 1;
+            }
+        }
+        assert_eq!(settled, self.settled, "wrong number of settled roots");
+        assert_eq!(
+            settled + self.unsettled,
+            roots,
+            "wrong number of unsettled roots"
+        );
+        #[cfg(feature = "stats")]
+        {
+            let stats = self.stats();
+            assert_eq!(
+                (stats.roots, stats.marked),
+                (roots, marked),
+                "wrong statistics"
+            );
+        }
+    }
+}
+
+impl HeapTag {
+    /// Creates the tag for a new heap.
+    fn new() -> Shared<HeapTag> {
+        Shared::new(HeapTag {
+            melded_into: None,
+            rank: 0,
+        })
+    }
+
+    /// Checks whether a handle with tag `handle_tag` belongs to the heap with tag `heap_tag` (which
+    /// is `None` for heaps that never contained any elements).
+    fn belongs_to(handle_tag: &Shared<HeapTag>, heap_tag: Option<&Shared<HeapTag>>) -> bool {
+        match heap_tag {
+            Some(tag) => Shared::ptr_eq(&HeapTag::find(handle_tag), tag),
+            None => false,
+        }
+    }
+
+    /// Follows the `melded_into` pointers starting at `tag`, and returns the tag at the end of the
+    /// chain. This is the tag of the heap that currently contains the nodes of the heap that
+    /// `tag` originally belonged to.
+    fn find(tag: &Shared<HeapTag>) -> Shared<HeapTag> {
+        let mut tag = tag.clone();
+        loop {
+            let next = match &tag.borrow().melded_into {
+                Some(next) => next.clone(),
+                None => return tag.clone(),
+            };
+            tag = next;
+        }
+    }
+
+    /// Returns the tag for the heap that results from melding two heaps with the given tags.
+    fn union(
+        tag1: Option<Shared<HeapTag>>,
+        tag2: Option<Shared<HeapTag>>,
+    ) -> Option<Shared<HeapTag>> {
+        let (tag1, tag2) = match (tag1, tag2) {
+            (Some(tag1), Some(tag2)) => (tag1, tag2),
+            (tag1, tag2) => return tag1.or(tag2),
+        };
+        let rank1 = tag1.borrow().rank;
+        let rank2 = tag2.borrow().rank;
+        let (parent, child) = if rank1 < rank2 {
+            (tag2, tag1)
+        } else {
+            (tag1, tag2)
+        };
+        if rank1 == rank2 {
+            parent.borrow_mut().rank += 1;
+        }
+        child.borrow_mut().melded_into = Some(parent.clone());
+        Some(parent)
+    }
+}
+
 impl<T> Drop for FibonacciHeap<T> {
     fn drop(&mut self) {
-        unsafe fn drop_recursive<T>(mut elem: *mut Node<T>) {
-            if elem.is_null() {
-                return;
+        /// Frees `elem`, after pushing the rest of its list and its children onto `stack`. All
+        /// lists on the stack are linear (not circular), so that we know where they end.
+        unsafe fn free_node<T>(stack: &mut Vec<*mut Node<T>>, elem: *mut Node<T>) {
+            if !(*elem).right.is_null() {
+                stack.push((*elem).right);
             }
-            (*(*elem).left).right = std::ptr::null_mut();
-            loop {
-                drop_recursive((*elem).child);
-                if (*elem).right.is_null() {
-                    let _ = Box::from_raw(elem);
-                    break;
+            let child = (*elem).child;
+            if !child.is_null() {
+                (*(*child).left).right = std::ptr::null_mut();
+                stack.push(child);
+            }
+            let node = Box::from_raw(elem);
+            node.outside_ref.borrow_mut().invalidated = true;
+        }
+
+        /// If dropping a key panics, this guard frees the remaining nodes while unwinding.
+        struct DropGuard<'a, T>(&'a mut Vec<*mut Node<T>>);
+
+        impl<T> Drop for DropGuard<'_, T> {
+            fn drop(&mut self) {
+                while let Some(elem) = self.0.pop() {
+                    unsafe { free_node(self.0, elem) };
                 }
-                (*elem).outside_ref.borrow_mut().invalidated = true;
-                elem = (*elem).right;
-                let _ = Box::from_raw((*elem).left);
             }
         }
-        unsafe {
-            drop_recursive(self.min);
+
+        // Trees can be arbitrarily deep (a long enough sequence of `decrease_key`s and `pop`s can
+        // produce a tree of height O(n)), so we must not recurse here. Instead, we keep an explicit
+        // stack of the lists that still have to be freed.
+        let mut stack: Vec<*mut Node<T>> = vec![];
+        if !self.min.is_null() {
+            unsafe { (*(*self.min).left).right = std::ptr::null_mut() };
+            stack.push(self.min);
+        }
+        while let Some(elem) = stack.pop() {
+            let guard = DropGuard(&mut stack);
+            unsafe { free_node(guard.0, elem) };
+            std::mem::forget(guard);
         }
     }
 }
 
 impl<T: Ord, const N: usize> From<[T; N]> for FibonacciHeap<T> {
-    /// Constructs a Fibonacci heap from an array of items.
+    /// Constructs a Fibonacci heap from an array of items, with
+    /// [heapify](FibonacciHeap::heapify).
     fn from(elems: [T; N]) -> Self {
-        let mut heap = FibonacciHeap::new();
-        for elem in elems {
-            heap.push(elem);
-        }
-        heap
+        FibonacciHeap::build(Vec::from(elems), |_| {})
     }
 }
 
 impl<T: Ord> From<Vec<T>> for FibonacciHeap<T> {
-    /// Constructs a Fibonacci heap from a `Vec` of items.
+    /// Constructs a Fibonacci heap from a `Vec` of items, with [heapify](FibonacciHeap::heapify).
     fn from(elems: Vec<T>) -> Self {
-        let mut heap = FibonacciHeap::new();
-        for elem in elems {
-            heap.push(elem);
-        }
-        heap
+        FibonacciHeap::build(elems, |_| {})
     }
 }
 
@@ -572,6 +1093,22 @@ impl<T: Ord> From<FibonacciHeap<T>> for Vec<T> {
     }
 }
 
+// SAFETY: the nodes of the heap are owned by the heap, and they are only ever accessed through
+// `&mut self` (or through `&self` for reading). The only things that are shared between the heap
+// and its handles are the `Shared` pointers, which are `Arc<Mutex<..>>`s when the `sync` feature is
+// enabled.
+#[cfg(feature = "sync")]
+unsafe impl<T: Send> Send for FibonacciHeap<T> {}
+#[cfg(feature = "sync")]
+unsafe impl<T: Sync> Sync for FibonacciHeap<T> {}
+
+// SAFETY: a handle can only be used to access the node it points to through a `&mut` reference to
+// the heap containing that node, and all other state of the handle is behind an `Arc<Mutex<..>>`.
+#[cfg(feature = "sync")]
+unsafe impl<T: Send> Send for NodePtr<T> {}
+#[cfg(feature = "sync")]
+unsafe impl<T: Send> Sync for NodePtr<T> {}
+
 impl<T: Ord> Default for FibonacciHeap<T> {
     /// Creates an empty `FibonacciHeap<T>`.
     fn default() -> FibonacciHeap<T> {
@@ -579,5 +1116,7 @@ impl<T: Ord> Default for FibonacciHeap<T> {
     }
 }
 
+// the oldest tests still use `min_value()`
 #[cfg(test)]
+#[allow(clippy::legacy_numeric_constants)]
 mod tests;
@@ -0,0 +1,86 @@
+use std::iter::FusedIterator;
+
+use crate::FibonacciHeap;
+
+/// Merges sorted iterators into a single sorted iterator.
+///
+/// The heap contains the next element of every iterator that is not exhausted yet, along with the
+/// index of that iterator. Every call to `next` pops the minimum, and pushes the next element of
+/// the iterator it came from. So merging `k` iterators with `n` elements in total takes
+/// O(n log k) time, and the iterators are advanced lazily.
+///
+/// Elements that are equal come out in the order of the iterators they come from, so the merge is
+/// stable. If some of the iterators are not sorted, the output is not sorted either (but it still
+/// contains all elements).
+///
+/// # Examples
+///
+/// ```
+/// use fibonacci_heap_rust::merge_sorted;
+///
+/// let shards = vec![vec![1, 4, 9], vec![2, 3, 10], vec![], vec![5]];
+/// let merged: Vec<_> = merge_sorted(shards).collect();
+/// assert_eq!(merged, vec![1, 2, 3, 4, 5, 9, 10]);
+/// ```
+pub fn merge_sorted<I>(iters: impl IntoIterator<Item = I>) -> MergeSorted<I::IntoIter>
+where
+    I: IntoIterator,
+    I::Item: Ord,
+{
+    let mut heap = FibonacciHeap::new();
+    let iters: Vec<_> = iters
+        .into_iter()
+        .enumerate()
+        .map(|(index, iter)| {
+            let mut iter = iter.into_iter();
+            if let Some(head) = iter.next() {
+                heap.push((head, index));
+            }
+            iter
+        })
+        .collect();
+    MergeSorted { heap, iters }
+}
+
+/// The iterator returned by [merge_sorted].
+pub struct MergeSorted<I: Iterator> {
+    /// The next element of every iterator that is not exhausted yet, with the index of the iterator.
+    heap: FibonacciHeap<(I::Item, usize)>,
+    iters: Vec<I>,
+}
+
+impl<I> Iterator for MergeSorted<I>
+where
+    I: Iterator,
+    I::Item: Ord,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        let (item, index) = self.heap.pop()?;
+        if let Some(next) = self.iters[index].next() {
+            self.heap.push((next, index));
+        }
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iters.iter().fold(
+            (self.heap.len(), Some(self.heap.len())),
+            |(low, high), iter| {
+                let (iter_low, iter_high) = iter.size_hint();
+                let high = high.zip(iter_high).and_then(|(a, b)| a.checked_add(b));
+                (low.saturating_add(iter_low), high)
+            },
+        )
+    }
+}
+
+/// A source iterator that returned `None` is not advanced anymore, so this is fused even if the
+/// sources are not.
+impl<I> FusedIterator for MergeSorted<I>
+where
+    I: Iterator,
+    I::Item: Ord,
+{
+}
@@ -0,0 +1,513 @@
+use crate::{HeapTag, Shared};
+
+/// A min-pairing heap, with the same handles as [FibonacciHeap](crate::FibonacciHeap): pushing an
+/// element returns a [PairingPtr], which can be used to decrease its key or to delete it, and which
+/// keeps track of whether the element is still in the heap.
+///
+/// Pairing heaps are much simpler than Fibonacci heaps (a node has no degree and no mark), and are
+/// often faster in practice. `push` and melding take O(1) time, `pop` and `delete` take O(log n)
+/// amortized time, and `decrease_key` takes o(log n) amortized time (it is O(1) in practice, but
+/// no constant bound has been proven).
+///
+/// Like [FibonacciHeap](crate::FibonacciHeap), the heap is left in a consistent state if the `Ord`
+/// or `Drop` implementation of `T` panics, and the only thing that may be off afterwards is the
+/// next element that is popped.
+///
+/// # Examples
+///
+/// ```
+/// use fibonacci_heap_rust::PairingHeap;
+///
+/// let mut heap = PairingHeap::new();
+/// heap.push(5);
+/// let seven = heap.push(7);
+/// let three = heap.push(3);
+/// heap.decrease_key(&seven, 1);
+/// assert_eq!(heap.delete(three), Some(3));
+/// assert_eq!(heap.pop(), Some(1));
+/// assert_eq!(heap.pop(), Some(5));
+/// assert_eq!(heap.pop(), None);
+/// ```
+pub struct PairingHeap<T> {
+    /// The current number of nodes in the heap.
+    n: usize,
+    /// The first root in the list of roots. There is only one root, except for a while during
+    /// `pop` and `delete`, and after a comparison panicked.
+    roots: *mut Node<T>,
+    /// The minimal root, or a null pointer if the heap is empty. This always points to a root, but
+    /// if a comparison panicked, not necessarily to the minimal one.
+    min: *mut Node<T>,
+    /// The tag that identifies this heap. It is created when the first element is pushed.
+    tag: Option<Shared<HeapTag>>,
+}
+
+/// A handle to an element inside a [PairingHeap], which works just like a
+/// [NodePtr](crate::NodePtr).
+pub struct PairingPtr<T>(Shared<PairingPtrInternal<T>>);
+
+struct PairingPtrInternal<T> {
+    /// Whether the node has been popped or deleted, or the heap has been dropped.
+    invalidated: bool,
+    ptr: *mut Node<T>,
+    /// The tag of the heap which this node was pushed into.
+    heap_tag: Shared<HeapTag>,
+}
+
+/// A node in the pairing heap. The children of a node, and the roots, form doubly linked lists
+/// that are not circular: `left` is null for the first node of a list, and `right` is null for
+/// the last one.
+struct Node<T> {
+    key: T,
+    /// The parent of this node, or a null pointer if it is a root.
+    parent: *mut Node<T>,
+    /// The first child of this node, or a null pointer if it has no children.
+    child: *mut Node<T>,
+    left: *mut Node<T>,
+    right: *mut Node<T>,
+    outside_ref: Shared<PairingPtrInternal<T>>,
+}
+
+impl<T> Clone for PairingPtr<T> {
+    fn clone(&self) -> PairingPtr<T> {
+        PairingPtr(self.0.clone())
+    }
+}
+
+impl<T: Ord> PairingHeap<T> {
+    /// Constructs a new, empty pairing heap.
+    pub const fn new() -> PairingHeap<T> {
+        PairingHeap {
+            n: 0,
+            roots: std::ptr::null_mut(),
+            min: std::ptr::null_mut(),
+            tag: None,
+        }
+    }
+
+    /// Checks whether the heap is empty.
+    pub fn is_empty(&self) -> bool {
+        self.min.is_null()
+    }
+
+    /// Returns the number of elements in the heap.
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    /// Returns a reference to the minimum element of the heap, or `None` if it is empty.
+    pub fn peek(&self) -> Option<&T> {
+        if self.min.is_null() {
+            None
+        } else {
+            unsafe { Some(&(*self.min).key) }
+        }
+    }
+
+    /// Produces a pairing heap from melding two existing pairing heaps, in O(1) time. The
+    /// [PairingPtr]s pointing to elements of either heap can be used with the new heap.
+    pub fn from_meld(mut heap1: PairingHeap<T>, mut heap2: PairingHeap<T>) -> PairingHeap<T> {
+        heap1.absorb(&mut heap2);
+        heap1
+    }
+
+    /// Moves all elements of `other` into this heap in O(1) time, leaving `other` empty.
+    ///
+    /// If the comparison of the two minimums panics, neither heap is changed.
+    pub(crate) fn absorb(&mut self, other: &mut PairingHeap<T>) {
+        if other.is_empty() {
+            return;
+        } else if self.is_empty() {
+            std::mem::swap(self, other);
+            return;
+        }
+        unsafe {
+            // compare before relinking anything, in case the comparison panics
+            let other_has_min = (*other.min).key < (*self.min).key;
+            // move the roots of `other` to the front of the root list (there is usually just one)
+            let mut last = other.roots;
+            while !(*last).right.is_null() {
+                last = (*last).right;
+            }
+            (*last).right = self.roots;
+            (*self.roots).left = last;
+            self.roots = other.roots;
+            let (winner, loser) = if other_has_min {
+                (other.min, self.min)
+            } else {
+                (self.min, other.min)
+            };
+            self.unlink(loser);
+            PairingHeap::add_child(winner, loser);
+            self.min = winner;
+        }
+        self.n += other.n;
+        self.tag = HeapTag::union(self.tag.take(), other.tag.take());
+        // the nodes now belong to `self`, so `other` must not free them when it is dropped
+        other.roots = std::ptr::null_mut();
+        other.min = std::ptr::null_mut();
+        other.n = 0;
+    }
+
+    /// Inserts an element into the heap, and returns a handle to it.
+    pub fn push(&mut self, item: T) -> PairingPtr<T> {
+        // compare before allocating anything, in case the comparison panics
+        let is_new_min = self.min.is_null() || unsafe { item < (*self.min).key };
+        let node: *mut Node<T> = Box::into_raw(Box::new(Node {
+            key: item,
+            parent: std::ptr::null_mut(),
+            child: std::ptr::null_mut(),
+            left: std::ptr::null_mut(),
+            right: std::ptr::null_mut(),
+            outside_ref: Shared::new(PairingPtrInternal {
+                invalidated: false,
+                ptr: std::ptr::null_mut(),
+                heap_tag: self.tag.get_or_insert_with(HeapTag::new).clone(),
+            }),
+        }));
+        unsafe {
+            (*node).outside_ref.borrow_mut().ptr = node;
+            if self.min.is_null() {
+                self.add_root(node);
+                self.min = node;
+            } else if is_new_min {
+                self.replace_min(node);
+            } else {
+                PairingHeap::add_child(self.min, node);
+            }
+            self.n += 1;
+            PairingPtr((*node).outside_ref.clone())
+        }
+    }
+
+    /// Decreases the key of the element that `elem` points to. If this element is not in the heap
+    /// anymore, or if `new_key` is larger than its key, nothing happens.
+    ///
+    /// Panics if `elem` points to an element of another heap.
+    pub fn decrease_key(&mut self, elem: &PairingPtr<T>, new_key: T) {
+        let Some(node) = self.node_of(elem, "decrease_key") else {
+            return;
+        };
+        unsafe {
+            if new_key > (*node).key {
+                return;
+            }
+            // do all comparisons before changing anything, in case one of them panics
+            let is_new_min = node != self.min && new_key < (*self.min).key;
+            let old_key = std::mem::replace(&mut (*node).key, new_key);
+            if !(*node).parent.is_null() {
+                // cut the subtree of the node, and link it with the minimal root
+                self.unlink(node);
+                if is_new_min {
+                    self.replace_min(node);
+                } else {
+                    PairingHeap::add_child(self.min, node);
+                }
+            } else if is_new_min {
+                self.min = node;
+            }
+            drop(old_key);
+        }
+    }
+
+    /// Deletes the element that `elem` points to, and returns it. If this element is not in the
+    /// heap anymore, nothing happens and `None` is returned.
+    ///
+    /// Panics if `elem` points to an element of another heap.
+    pub fn delete(&mut self, elem: PairingPtr<T>) -> Option<T> {
+        let node = self.node_of(&elem, "delete")?;
+        unsafe {
+            if !(*node).parent.is_null() {
+                self.unlink(node);
+                self.add_root(node);
+            }
+        }
+        self.min = node;
+        self.pop()
+    }
+
+    /// Extracts the minimum element from the heap and returns it.
+    pub fn pop(&mut self) -> Option<T> {
+        let popped = self.min;
+        if popped.is_null() {
+            return None;
+        }
+        unsafe {
+            (*popped).outside_ref.borrow_mut().invalidated = true;
+            self.unlink(popped);
+            // move the children of the popped node to the front of the root list
+            let first = (*popped).child;
+            if !first.is_null() {
+                let mut last = first;
+                loop {
+                    (*last).parent = std::ptr::null_mut();
+                    if (*last).right.is_null() {
+                        break;
+                    }
+                    last = (*last).right;
+                }
+                (*last).right = self.roots;
+                if !self.roots.is_null() {
+                    (*self.roots).left = last;
+                }
+                self.roots = first;
+            }
+            // The popped node is not in the heap anymore, so take ownership of it before pairing
+            // the roots: if a comparison panics, it is then freed while unwinding.
+            let popped = Box::from_raw(popped);
+            self.n -= 1;
+            self.min = self.roots;
+            if !self.roots.is_null() {
+                self.pair_roots();
+            }
+            Some(popped.key)
+        }
+    }
+
+    /// Links all roots into a single tree with the standard two-pass pairing: first link the roots
+    /// in pairs from left to right, then link the resulting trees from right to left. The root
+    /// list must not be empty.
+    unsafe fn pair_roots(&mut self) {
+        let mut node = self.roots;
+        let mut last = node;
+        while !node.is_null() {
+            let next = (*node).right;
+            if next.is_null() {
+                last = node;
+                break;
+            }
+            last = self.link(node, next);
+            node = (*last).right;
+        }
+        let mut tree = last;
+        while !(*tree).left.is_null() {
+            tree = self.link((*tree).left, tree);
+        }
+        self.min = tree;
+    }
+
+    /// Links the roots `a` and `b`: the one with the larger key becomes the first child of the
+    /// other one, which is returned. `self.min` is updated if it is the root that stops being one.
+    unsafe fn link(&mut self, a: *mut Node<T>, b: *mut Node<T>) -> *mut Node<T> {
+        // compare before relinking anything, in case the comparison panics
+        let (winner, loser) = if (*b).key < (*a).key { (b, a) } else { (a, b) };
+        self.unlink(loser);
+        PairingHeap::add_child(winner, loser);
+        if self.min == loser {
+            self.min = winner;
+        }
+        winner
+    }
+
+    /// Makes `node`, which must not be in any list, the new minimal root, with the old minimal root
+    /// as its first child.
+    unsafe fn replace_min(&mut self, node: *mut Node<T>) {
+        let old_min = self.min;
+        self.unlink(old_min);
+        self.add_root(node);
+        PairingHeap::add_child(node, old_min);
+        self.min = node;
+    }
+
+    /// Adds `node`, which must not be in any list, to the front of the root list.
+    unsafe fn add_root(&mut self, node: *mut Node<T>) {
+        (*node).right = self.roots;
+        if !self.roots.is_null() {
+            (*self.roots).left = node;
+        }
+        self.roots = node;
+    }
+
+    /// Makes `child`, which must not be in any list, the first child of `parent`.
+    unsafe fn add_child(parent: *mut Node<T>, child: *mut Node<T>) {
+        (*child).parent = parent;
+        (*child).right = (*parent).child;
+        if !(*parent).child.is_null() {
+            (*(*parent).child).left = child;
+        }
+        (*parent).child = child;
+    }
+
+    /// Removes `node` from the list it is in (the root list, or the child list of its parent),
+    /// along with its subtree.
+    unsafe fn unlink(&mut self, node: *mut Node<T>) {
+        let (left, right) = ((*node).left, (*node).right);
+        if !left.is_null() {
+            (*left).right = right;
+        } else if !(*node).parent.is_null() {
+            (*(*node).parent).child = right;
+        } else {
+            self.roots = right;
+        }
+        if !right.is_null() {
+            (*right).left = left;
+        }
+        (*node).left = std::ptr::null_mut();
+        (*node).right = std::ptr::null_mut();
+        (*node).parent = std::ptr::null_mut();
+    }
+
+    /// Returns a pointer to the node that `elem` points to, or `None` if that node is not in any
+    /// heap anymore. Panics if the node is in some other heap than this one.
+    fn node_of(&self, elem: &PairingPtr<T>, method: &str) -> Option<*mut Node<T>> {
+        let (node, heap_tag) = {
+            let elem = elem.0.borrow();
+            if elem.invalidated {
+                return None;
+            }
+            (elem.ptr, elem.heap_tag.clone())
+        };
+        if !HeapTag::belongs_to(&heap_tag, self.tag.as_ref()) {
+            panic!(
+                "Oh no... you called some_heap.{method}(..) on \
+                an element that was never inserted into this heap!"
+            )
+        }
+        Some(node)
+    }
+}
+
+#[cfg(test)]
+impl<T: Ord> PairingHeap<T> {
+    /// Panics if the heap is not a valid pairing heap, or if `min` is not the minimal root.
+    pub(crate) fn check_invariants(&self) {
+        self.check_structure();
+        let mut root = self.roots;
+        while !root.is_null() {
+            unsafe {
+                assert!((*root).key >= (*self.min).key, "`min` is not minimal");
+                root = (*root).right;
+            }
+        }
+    }
+
+    /// Panics if the heap is not a valid pairing heap, except that `min` does not have to point to
+    /// the minimal root (which is allowed after a comparison panicked).
+    pub(crate) fn check_structure(&self) {
+        if self.roots.is_null() {
+            assert!(self.min.is_null());
+            assert_eq!(self.n, 0);
+            return;
+        }
+        let mut count = 0;
+        let mut min_is_root = false;
+        // the lists that still have to be checked, along with their parents
+        let mut lists = vec![(self.roots, std::ptr::null_mut::<Node<T>>())];
+        while let Some((first, parent)) = lists.pop() {
+            unsafe {
+                assert!((*first).left.is_null(), "broken list");
+                let mut node = first;
+                while !node.is_null() {
+                    assert_eq!((*node).parent, parent, "wrong parent");
+                    if !(*node).right.is_null() {
+                        assert_eq!((*(*node).right).left, node, "broken list");
+                    }
+                    if parent.is_null() {
+                        min_is_root |= node == self.min;
+                    } else {
+                        assert!((*node).key >= (*parent).key, "heap order violated");
+                    }
+                    if !(*node).child.is_null() {
+                        lists.push(((*node).child, node));
+                    }
+                    let outside_ref = (*node).outside_ref.borrow();
+                    assert!(std::ptr::eq(outside_ref.ptr, node) && !outside_ref.invalidated);
+                    count += 1;
+                    assert!(count <= self.n, "more nodes than `n`");
+                    node = (*node).right;
+                }
+            }
+        }
+        assert!(min_is_root, "`min` is not a root");
+        assert_eq!(count, self.n);
+    }
+}
+
+impl<T> Drop for PairingHeap<T> {
+    fn drop(&mut self) {
+        /// Frees `node`, after pushing the rest of its list and its children onto `stack`.
+        unsafe fn free_node<T>(stack: &mut Vec<*mut Node<T>>, node: *mut Node<T>) {
+            if !(*node).right.is_null() {
+                stack.push((*node).right);
+            }
+            if !(*node).child.is_null() {
+                stack.push((*node).child);
+            }
+            let node = Box::from_raw(node);
+            node.outside_ref.borrow_mut().invalidated = true;
+        }
+
+        /// If dropping a key panics, this guard frees the remaining nodes while unwinding.
+        struct DropGuard<'a, T>(&'a mut Vec<*mut Node<T>>);
+
+        impl<T> Drop for DropGuard<'_, T> {
+            fn drop(&mut self) {
+                while let Some(node) = self.0.pop() {
+                    unsafe { free_node(self.0, node) };
+                }
+            }
+        }
+
+        // like in `FibonacciHeap`, trees can be arbitrarily deep, so we must not recurse here
+        let mut stack: Vec<*mut Node<T>> = vec![];
+        if !self.roots.is_null() {
+            stack.push(self.roots);
+        }
+        while let Some(node) = stack.pop() {
+            let guard = DropGuard(&mut stack);
+            unsafe { free_node(guard.0, node) };
+            std::mem::forget(guard);
+        }
+    }
+}
+
+impl<T: Ord, const N: usize> From<[T; N]> for PairingHeap<T> {
+    /// Constructs a pairing heap from an array of items.
+    fn from(elems: [T; N]) -> Self {
+        let mut heap = PairingHeap::new();
+        for elem in elems {
+            heap.push(elem);
+        }
+        heap
+    }
+}
+
+impl<T: Ord> From<Vec<T>> for PairingHeap<T> {
+    /// Constructs a pairing heap from a `Vec` of items.
+    fn from(elems: Vec<T>) -> Self {
+        let mut heap = PairingHeap::new();
+        for elem in elems {
+            heap.push(elem);
+        }
+        heap
+    }
+}
+
+impl<T: Ord> From<PairingHeap<T>> for Vec<T> {
+    /// Constructs a `Vec` of items from a `PairingHeap`. The items in the `Vec` are sorted in
+    /// increasing order (minimal element first). The pairing heap is consumed.
+    fn from(mut heap: PairingHeap<T>) -> Vec<T> {
+        let mut res = vec![];
+        while let Some(popped) = heap.pop() {
+            res.push(popped);
+        }
+        res
+    }
+}
+
+impl<T: Ord> Default for PairingHeap<T> {
+    /// Creates an empty `PairingHeap<T>`.
+    fn default() -> PairingHeap<T> {
+        PairingHeap::new()
+    }
+}
+
+// SAFETY: just like for `FibonacciHeap` and `NodePtr`, the nodes are only accessed through the
+// heap that owns them, and everything that is shared with the handles is behind `Arc<Mutex<..>>`s.
+#[cfg(feature = "sync")]
+unsafe impl<T: Send> Send for PairingHeap<T> {}
+#[cfg(feature = "sync")]
+unsafe impl<T: Sync> Sync for PairingHeap<T> {}
+#[cfg(feature = "sync")]
+unsafe impl<T: Send> Send for PairingPtr<T> {}
+#[cfg(feature = "sync")]
+unsafe impl<T: Send> Sync for PairingPtr<T> {}
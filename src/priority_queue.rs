@@ -0,0 +1,231 @@
+use crate::{
+    BinomialHeap, BinomialPtr, FibonacciHeap, IndexedBinaryHeap, IndexedPtr, NodePtr, PairingHeap,
+    PairingPtr,
+};
+
+/// A min-priority queue whose elements can be addressed by handles, to decrease their keys or to
+/// delete them. This lets you write code that works with any of the heaps in this crate, and
+/// choose the one that is the fastest for your workload:
+///
+/// - [FibonacciHeap]: O(1) amortized `push`, `decrease_key` and meld, and O(log n) amortized `pop`
+///   and `delete`;
+/// - [PairingHeap]: the same operations, with weaker proven bounds for `decrease_key`, but usually
+///   faster in practice;
+/// - [BinomialHeap]: O(log n) worst-case time for everything, including melding, for when a
+///   single slow operation hurts more than a slightly slower average;
+/// - [IndexedBinaryHeap]: O(log n) worst-case time for everything except melding, and compact
+///   storage.
+///
+/// All implementations in this crate behave the same way with respect to handles: a handle to an
+/// element that has been popped or deleted is ignored, handles to elements of a melded heap can be
+/// used with the heap that it was melded into, and using a handle with a heap that its element was
+/// never in panics.
+///
+/// # Examples
+///
+/// ```
+/// use fibonacci_heap_rust::{
+///     AddressablePriorityQueue, BinomialHeap, FibonacciHeap, IndexedBinaryHeap, PairingHeap,
+/// };
+///
+/// /// Pushes some elements, changes some of them, and returns them in order.
+/// fn exercise<Q: AddressablePriorityQueue<Item = i32> + Default>() -> Vec<i32> {
+///     let mut queue = Q::default();
+///     let handles: Vec<_> = [5, 8, 2, 9].into_iter().map(|key| queue.push(key)).collect();
+///     queue.decrease_key(&handles[1], 1);
+///     queue.delete(handles[2].clone());
+///     let mut other = Q::default();
+///     other.push(3);
+///     queue.meld(other);
+///     std::iter::from_fn(|| queue.pop()).collect()
+/// }
+///
+/// assert_eq!(exercise::<FibonacciHeap<i32>>(), vec![1, 3, 5, 9]);
+/// assert_eq!(exercise::<PairingHeap<i32>>(), vec![1, 3, 5, 9]);
+/// assert_eq!(exercise::<BinomialHeap<i32>>(), vec![1, 3, 5, 9]);
+/// assert_eq!(exercise::<IndexedBinaryHeap<i32>>(), vec![1, 3, 5, 9]);
+/// ```
+pub trait AddressablePriorityQueue {
+    /// The type of the elements.
+    type Item: Ord;
+    /// The type of the handles that are returned by [push](AddressablePriorityQueue::push).
+    type Handle: Clone;
+
+    /// Inserts an element, and returns a handle to it.
+    fn push(&mut self, item: Self::Item) -> Self::Handle;
+
+    /// Removes the minimum element and returns it, or returns `None` if the queue is empty.
+    fn pop(&mut self) -> Option<Self::Item>;
+
+    /// Returns a reference to the minimum element, or `None` if the queue is empty.
+    fn peek(&self) -> Option<&Self::Item>;
+
+    /// Decreases the key of the element that `handle` points to. If this element is not in the
+    /// queue anymore, or if `new_key` is larger than its key, nothing happens.
+    fn decrease_key(&mut self, handle: &Self::Handle, new_key: Self::Item);
+
+    /// Deletes the element that `handle` points to, and returns it. If this element is not in the
+    /// queue anymore, nothing happens and `None` is returned.
+    fn delete(&mut self, handle: Self::Handle) -> Option<Self::Item>;
+
+    /// Moves all elements of `other` into this queue.
+    fn meld(&mut self, other: Self)
+    where
+        Self: Sized;
+
+    /// Returns the number of elements.
+    fn len(&self) -> usize;
+
+    /// Checks whether the queue is empty.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T: Ord> AddressablePriorityQueue for FibonacciHeap<T> {
+    type Item = T;
+    type Handle = NodePtr<T>;
+
+    fn push(&mut self, item: T) -> NodePtr<T> {
+        FibonacciHeap::push(self, item)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        FibonacciHeap::pop(self)
+    }
+
+    fn peek(&self) -> Option<&T> {
+        FibonacciHeap::peek(self)
+    }
+
+    fn decrease_key(&mut self, handle: &NodePtr<T>, new_key: T) {
+        FibonacciHeap::decrease_key(self, handle, new_key)
+    }
+
+    fn delete(&mut self, handle: NodePtr<T>) -> Option<T> {
+        FibonacciHeap::delete(self, handle)
+    }
+
+    fn meld(&mut self, mut other: FibonacciHeap<T>) {
+        self.absorb(&mut other);
+    }
+
+    fn len(&self) -> usize {
+        FibonacciHeap::len(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        FibonacciHeap::is_empty(self)
+    }
+}
+
+impl<T: Ord> AddressablePriorityQueue for PairingHeap<T> {
+    type Item = T;
+    type Handle = PairingPtr<T>;
+
+    fn push(&mut self, item: T) -> PairingPtr<T> {
+        PairingHeap::push(self, item)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        PairingHeap::pop(self)
+    }
+
+    fn peek(&self) -> Option<&T> {
+        PairingHeap::peek(self)
+    }
+
+    fn decrease_key(&mut self, handle: &PairingPtr<T>, new_key: T) {
+        PairingHeap::decrease_key(self, handle, new_key)
+    }
+
+    fn delete(&mut self, handle: PairingPtr<T>) -> Option<T> {
+        PairingHeap::delete(self, handle)
+    }
+
+    fn meld(&mut self, mut other: PairingHeap<T>) {
+        self.absorb(&mut other);
+    }
+
+    fn len(&self) -> usize {
+        PairingHeap::len(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        PairingHeap::is_empty(self)
+    }
+}
+
+impl<T: Ord> AddressablePriorityQueue for BinomialHeap<T> {
+    type Item = T;
+    type Handle = BinomialPtr<T>;
+
+    fn push(&mut self, item: T) -> BinomialPtr<T> {
+        BinomialHeap::push(self, item)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        BinomialHeap::pop(self)
+    }
+
+    fn peek(&self) -> Option<&T> {
+        BinomialHeap::peek(self)
+    }
+
+    fn decrease_key(&mut self, handle: &BinomialPtr<T>, new_key: T) {
+        BinomialHeap::decrease_key(self, handle, new_key)
+    }
+
+    fn delete(&mut self, handle: BinomialPtr<T>) -> Option<T> {
+        BinomialHeap::delete(self, handle)
+    }
+
+    fn meld(&mut self, mut other: BinomialHeap<T>) {
+        self.absorb(&mut other);
+    }
+
+    fn len(&self) -> usize {
+        BinomialHeap::len(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        BinomialHeap::is_empty(self)
+    }
+}
+
+impl<T: Ord> AddressablePriorityQueue for IndexedBinaryHeap<T> {
+    type Item = T;
+    type Handle = IndexedPtr;
+
+    fn push(&mut self, item: T) -> IndexedPtr {
+        IndexedBinaryHeap::push(self, item)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        IndexedBinaryHeap::pop(self)
+    }
+
+    fn peek(&self) -> Option<&T> {
+        IndexedBinaryHeap::peek(self)
+    }
+
+    fn decrease_key(&mut self, handle: &IndexedPtr, new_key: T) {
+        IndexedBinaryHeap::decrease_key(self, handle, new_key)
+    }
+
+    fn delete(&mut self, handle: IndexedPtr) -> Option<T> {
+        IndexedBinaryHeap::delete(self, handle)
+    }
+
+    fn meld(&mut self, mut other: IndexedBinaryHeap<T>) {
+        self.absorb(&mut other);
+    }
+
+    fn len(&self) -> usize {
+        IndexedBinaryHeap::len(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        IndexedBinaryHeap::is_empty(self)
+    }
+}
@@ -0,0 +1,92 @@
+use crate::FibonacciHeap;
+
+impl<T: Ord> FibonacciHeap<T> {
+    /// Consumes the heap, and returns its `k` smallest elements in sorted order (or all of them, if
+    /// there are fewer than `k`). The other elements are dropped.
+    ///
+    /// This takes O(k log n) amortized time, since the heap only has to be consolidated `k` times.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fibonacci_heap_rust::FibonacciHeap;
+    ///
+    /// let heap = FibonacciHeap::from([5, 1, 4, 2, 3]);
+    /// assert_eq!(heap.into_smallest_k(3), vec![1, 2, 3]);
+    /// ```
+    pub fn into_smallest_k(mut self, k: usize) -> Vec<T> {
+        let mut smallest = Vec::with_capacity(k.min(self.len()));
+        while smallest.len() < k {
+            match self.pop() {
+                Some(item) => smallest.push(item),
+                None => break,
+            }
+        }
+        smallest
+    }
+
+    /// Inserts an element into the heap, unless it already contains `capacity` (or more) elements:
+    /// then the smallest of `item` and the minimum of the heap is returned instead. So if you only
+    /// push with `push_bounded`, the heap contains the `capacity` largest elements pushed so far.
+    ///
+    /// To keep the `capacity` smallest elements instead (evicting the largest), use a heap of
+    /// [Reverse](std::cmp::Reverse) elements.
+    ///
+    /// Note that no [NodePtr](crate::NodePtr) is returned, because `item` may be evicted right
+    /// away.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fibonacci_heap_rust::FibonacciHeap;
+    /// use std::cmp::Reverse;
+    ///
+    /// // a leaderboard of the 3 highest scores
+    /// let mut top = FibonacciHeap::new();
+    /// for score in [50, 80, 20, 90, 70] {
+    ///     top.push_bounded(score, 3);
+    /// }
+    /// assert_eq!(Vec::from(top), vec![70, 80, 90]);
+    ///
+    /// // the 2 fastest times, evicting the slowest
+    /// let mut fastest = FibonacciHeap::new();
+    /// assert_eq!(fastest.push_bounded(Reverse(12), 2), None);
+    /// assert_eq!(fastest.push_bounded(Reverse(10), 2), None);
+    /// assert_eq!(fastest.push_bounded(Reverse(11), 2), Some(Reverse(12)));
+    /// ```
+    pub fn push_bounded(&mut self, item: T, capacity: usize) -> Option<T> {
+        if self.len() < capacity {
+            self.push(item);
+            return None;
+        }
+        match self.peek() {
+            Some(min) if *min < item => {
+                self.push(item);
+                self.pop()
+            }
+            _ => Some(item),
+        }
+    }
+}
+
+/// Returns the `k` smallest elements of `iter` in sorted order (or all of them, if there are fewer
+/// than `k`).
+///
+/// All elements are pushed into a [FibonacciHeap] first, which takes O(1) time each, and then only
+/// `k` of them are popped. So this takes O(n + k log n) time in total.
+///
+/// # Examples
+///
+/// ```
+/// use fibonacci_heap_rust::smallest_k;
+///
+/// let words = ["pear", "apple", "fig", "banana", "cherry"];
+/// assert_eq!(smallest_k(words, 2), vec!["apple", "banana"]);
+/// ```
+pub fn smallest_k<T: Ord>(iter: impl IntoIterator<Item = T>, k: usize) -> Vec<T> {
+    let mut heap = FibonacciHeap::new();
+    for item in iter {
+        heap.push(item);
+    }
+    heap.into_smallest_k(k)
+}
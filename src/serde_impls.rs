@@ -0,0 +1,142 @@
+//! `Serialize` and `Deserialize` for [FibonacciHeap], with the `serde` feature enabled.
+//!
+//! By default, a heap is serialized as the sequence of its elements. The exact shape of the forest
+//! can be serialized too, with [serialize_forest](FibonacciHeap::serialize_forest) and
+//! [deserialize_forest](FibonacciHeap::deserialize_forest).
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde::de::{Deserialize, Deserializer, Error, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeSeq, Serializer};
+
+use crate::{FibonacciHeap, HeapWithHandles};
+
+impl<T: Ord + Serialize> Serialize for FibonacciHeap<T> {
+    /// Serializes the elements of the heap as a sequence, in no particular order.
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        let mut result = Ok(());
+        self.for_each_in_preorder(|key, _, _| {
+            if result.is_ok() {
+                result = seq.serialize_element(key);
+            }
+        });
+        result?;
+        seq.end()
+    }
+}
+
+impl<'de, T: Ord + Deserialize<'de>> Deserialize<'de> for FibonacciHeap<T> {
+    /// Deserializes a heap from a sequence of elements.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<FibonacciHeap<T>, D::Error> {
+        Ok(FibonacciHeap::deserialize_with_handles(deserializer)?.0)
+    }
+}
+
+impl<T: Ord> FibonacciHeap<T> {
+    /// Deserializes a heap from a sequence of elements, like its `Deserialize` implementation, but
+    /// also returns a handle to every element, in the order of the sequence. This lets you rebuild
+    /// your own tables of handles after loading a heap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fibonacci_heap_rust::FibonacciHeap;
+    ///
+    /// let json = "[3, 1, 2]";
+    /// let mut deserializer = serde_json::Deserializer::from_str(json);
+    /// let (mut heap, handles) = FibonacciHeap::deserialize_with_handles(&mut deserializer).unwrap();
+    /// heap.decrease_key(&handles[0], 0); // the 3
+    /// assert_eq!(Vec::from(heap), vec![0, 1, 2]);
+    /// ```
+    pub fn deserialize_with_handles<'de, D>(deserializer: D) -> Result<HeapWithHandles<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Deserialize<'de>,
+    {
+        struct ElementsVisitor<T>(PhantomData<T>);
+
+        impl<'de, T: Ord + Deserialize<'de>> Visitor<'de> for ElementsVisitor<T> {
+            type Value = HeapWithHandles<T>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a sequence of elements")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut heap = FibonacciHeap::new();
+                let mut handles = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+                while let Some(elem) = seq.next_element()? {
+                    handles.push(heap.push(elem));
+                }
+                Ok((heap, handles))
+            }
+        }
+
+        deserializer.deserialize_seq(ElementsVisitor(PhantomData))
+    }
+
+    /// Serializes the exact shape of the heap, so that it can be restored with
+    /// [deserialize_forest](FibonacciHeap::deserialize_forest), including the amortized state
+    /// (which nodes are linked, and which are marked).
+    ///
+    /// The forest is serialized as the sequence of its nodes in preorder (starting at the minimal
+    /// root), where every node is a `(key, degree, mark)` tuple and is directly followed by its
+    /// `degree` subtrees. This can be used with `#[serde(serialize_with = "..")]`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fibonacci_heap_rust::FibonacciHeap;
+    ///
+    /// let mut heap = FibonacciHeap::from([4, 1, 3, 2]);
+    /// heap.pop(); // links the other three nodes into two trees
+    /// let mut json = vec![];
+    /// heap.serialize_forest(&mut serde_json::Serializer::new(&mut json)).unwrap();
+    /// assert_eq!(String::from_utf8(json).unwrap(), "[[2,1,false],[3,0,false],[4,0,false]]");
+    /// ```
+    pub fn serialize_forest<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize,
+    {
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        let mut result = Ok(());
+        self.for_each_in_preorder(|key, degree, mark| {
+            if result.is_ok() {
+                result = seq.serialize_element(&(key, degree, mark));
+            }
+        });
+        result?;
+        seq.end()
+    }
+
+    /// Deserializes a heap that was serialized with
+    /// [serialize_forest](FibonacciHeap::serialize_forest), restoring its exact shape. This can be
+    /// used with `#[serde(deserialize_with = "..")]`.
+    ///
+    /// The input is validated, and an error is returned if it does not describe a valid Fibonacci
+    /// heap (for example, if the keys are not in heap order, or if a degree does not match the
+    /// number of children).
+    pub fn deserialize_forest<'de, D>(deserializer: D) -> Result<FibonacciHeap<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Deserialize<'de>,
+    {
+        Ok(FibonacciHeap::deserialize_forest_with_handles(deserializer)?.0)
+    }
+
+    /// Like [deserialize_forest](FibonacciHeap::deserialize_forest), but also returns a handle to
+    /// every node, in the order in which the nodes were serialized.
+    pub fn deserialize_forest_with_handles<'de, D>(
+        deserializer: D,
+    ) -> Result<HeapWithHandles<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Deserialize<'de>,
+    {
+        let nodes = Vec::<(T, usize, bool)>::deserialize(deserializer)?;
+        FibonacciHeap::from_preorder(nodes)
+            .map_err(|message| D::Error::custom(format_args!("invalid Fibonacci heap: {message}")))
+    }
+}
@@ -0,0 +1,65 @@
+//! The reference-counted, interiorly mutable pointer that is shared between the nodes of a
+//! Fibonacci heap and the [NodePtr](crate::NodePtr)s handed out to the user.
+//!
+//! By default, this is just an `Rc<RefCell<T>>`. With the `sync` feature enabled, it is an
+//! `Arc<Mutex<T>>` instead, so that heaps and their handles can be moved to other threads.
+
+use std::ops::{Deref, DerefMut};
+
+#[cfg(not(feature = "sync"))]
+pub(crate) struct Shared<T>(std::rc::Rc<std::cell::RefCell<T>>);
+
+#[cfg(feature = "sync")]
+pub(crate) struct Shared<T>(std::sync::Arc<std::sync::Mutex<T>>);
+
+#[cfg(not(feature = "sync"))]
+impl<T> Shared<T> {
+    pub(crate) fn new(value: T) -> Shared<T> {
+        Shared(std::rc::Rc::new(std::cell::RefCell::new(value)))
+    }
+
+    pub(crate) fn borrow(&self) -> impl Deref<Target = T> + '_ {
+        self.0.borrow()
+    }
+
+    pub(crate) fn borrow_mut(&self) -> impl DerefMut<Target = T> + '_ {
+        self.0.borrow_mut()
+    }
+
+    /// Returns true if and only if both pointers point to the same allocation.
+    pub(crate) fn ptr_eq(this: &Shared<T>, other: &Shared<T>) -> bool {
+        std::rc::Rc::ptr_eq(&this.0, &other.0)
+    }
+}
+
+#[cfg(feature = "sync")]
+impl<T> Shared<T> {
+    pub(crate) fn new(value: T) -> Shared<T> {
+        Shared(std::sync::Arc::new(std::sync::Mutex::new(value)))
+    }
+
+    pub(crate) fn borrow(&self) -> impl Deref<Target = T> + '_ {
+        // The mutex only gets poisoned if some code panics while holding the lock, and none of
+        // the code in this crate can panic while it holds the lock.
+        self.0
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    pub(crate) fn borrow_mut(&self) -> impl DerefMut<Target = T> + '_ {
+        self.0
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Returns true if and only if both pointers point to the same allocation.
+    pub(crate) fn ptr_eq(this: &Shared<T>, other: &Shared<T>) -> bool {
+        std::sync::Arc::ptr_eq(&this.0, &other.0)
+    }
+}
+
+impl<T> Clone for Shared<T> {
+    fn clone(&self) -> Shared<T> {
+        Shared(self.0.clone())
+    }
+}
@@ -0,0 +1,380 @@
+//! A compact binary format that stores the exact shape of a [FibonacciHeap], so that a restored
+//! heap has the same trees and marks (and so the same amortized state) as the original one.
+
+use std::cmp::Reverse;
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Read, Write};
+
+use crate::{FibonacciHeap, HeapWithHandles};
+
+const MAGIC: [u8; 4] = *b"FIBH";
+const VERSION: u8 = 1;
+
+/// A key that can be written to and read from a snapshot of a [FibonacciHeap].
+///
+/// This is implemented for the primitive integer types (in little endian), `bool`, `char`,
+/// `String` and `Vec<u8>` (prefixed with their length), `Reverse<T>`, and pairs and triples.
+pub trait SnapshotKey: Sized {
+    /// Writes the key to `writer`.
+    fn write_key(&self, writer: &mut dyn Write) -> io::Result<()>;
+
+    /// Reads a key that was written by [write_key](SnapshotKey::write_key) from `reader`.
+    ///
+    /// Returns an error of kind [InvalidData](io::ErrorKind::InvalidData) if the input is not a
+    /// valid key.
+    fn read_key(reader: &mut dyn Read) -> io::Result<Self>;
+}
+
+macro_rules! impl_snapshot_key_for_integers {
+    ($($t:ty),*) => {
+        $(impl SnapshotKey for $t {
+            fn write_key(&self, writer: &mut dyn Write) -> io::Result<()> {
+                writer.write_all(&self.to_le_bytes())
+            }
+
+            fn read_key(reader: &mut dyn Read) -> io::Result<$t> {
+                let mut bytes = [0; std::mem::size_of::<$t>()];
+                reader.read_exact(&mut bytes)?;
+                Ok(<$t>::from_le_bytes(bytes))
+            }
+        })*
+    };
+}
+
+impl_snapshot_key_for_integers!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+impl SnapshotKey for usize {
+    /// Written as a `u64`, so that snapshots can be read on platforms with another pointer width.
+    fn write_key(&self, writer: &mut dyn Write) -> io::Result<()> {
+        (*self as u64).write_key(writer)
+    }
+
+    fn read_key(reader: &mut dyn Read) -> io::Result<usize> {
+        usize::try_from(u64::read_key(reader)?).map_err(invalid_data)
+    }
+}
+
+impl SnapshotKey for isize {
+    /// Written as an `i64`, so that snapshots can be read on platforms with another pointer width.
+    fn write_key(&self, writer: &mut dyn Write) -> io::Result<()> {
+        (*self as i64).write_key(writer)
+    }
+
+    fn read_key(reader: &mut dyn Read) -> io::Result<isize> {
+        isize::try_from(i64::read_key(reader)?).map_err(invalid_data)
+    }
+}
+
+impl SnapshotKey for bool {
+    fn write_key(&self, writer: &mut dyn Write) -> io::Result<()> {
+        (*self as u8).write_key(writer)
+    }
+
+    fn read_key(reader: &mut dyn Read) -> io::Result<bool> {
+        match u8::read_key(reader)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(invalid_data("invalid bool")),
+        }
+    }
+}
+
+impl SnapshotKey for char {
+    fn write_key(&self, writer: &mut dyn Write) -> io::Result<()> {
+        (*self as u32).write_key(writer)
+    }
+
+    fn read_key(reader: &mut dyn Read) -> io::Result<char> {
+        char::from_u32(u32::read_key(reader)?).ok_or_else(|| invalid_data("invalid char"))
+    }
+}
+
+impl SnapshotKey for Vec<u8> {
+    fn write_key(&self, writer: &mut dyn Write) -> io::Result<()> {
+        write_varint(writer, self.len() as u64)?;
+        writer.write_all(self)
+    }
+
+    fn read_key(reader: &mut dyn Read) -> io::Result<Vec<u8>> {
+        let len = read_varint(reader)?;
+        let mut bytes = vec![];
+        // don't trust the length for the allocation, the input may be corrupt
+        reader.take(len).read_to_end(&mut bytes)?;
+        if bytes.len() as u64 != len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok(bytes)
+    }
+}
+
+impl SnapshotKey for String {
+    fn write_key(&self, writer: &mut dyn Write) -> io::Result<()> {
+        write_varint(writer, self.len() as u64)?;
+        writer.write_all(self.as_bytes())
+    }
+
+    fn read_key(reader: &mut dyn Read) -> io::Result<String> {
+        String::from_utf8(Vec::read_key(reader)?).map_err(invalid_data)
+    }
+}
+
+impl<T: SnapshotKey> SnapshotKey for Reverse<T> {
+    fn write_key(&self, writer: &mut dyn Write) -> io::Result<()> {
+        self.0.write_key(writer)
+    }
+
+    fn read_key(reader: &mut dyn Read) -> io::Result<Reverse<T>> {
+        Ok(Reverse(T::read_key(reader)?))
+    }
+}
+
+impl<A: SnapshotKey, B: SnapshotKey> SnapshotKey for (A, B) {
+    fn write_key(&self, writer: &mut dyn Write) -> io::Result<()> {
+        self.0.write_key(writer)?;
+        self.1.write_key(writer)
+    }
+
+    fn read_key(reader: &mut dyn Read) -> io::Result<(A, B)> {
+        Ok((A::read_key(reader)?, B::read_key(reader)?))
+    }
+}
+
+impl<A: SnapshotKey, B: SnapshotKey, C: SnapshotKey> SnapshotKey for (A, B, C) {
+    fn write_key(&self, writer: &mut dyn Write) -> io::Result<()> {
+        self.0.write_key(writer)?;
+        self.1.write_key(writer)?;
+        self.2.write_key(writer)
+    }
+
+    fn read_key(reader: &mut dyn Read) -> io::Result<(A, B, C)> {
+        Ok((
+            A::read_key(reader)?,
+            B::read_key(reader)?,
+            C::read_key(reader)?,
+        ))
+    }
+}
+
+/// The error returned when a snapshot cannot be read.
+#[derive(Debug)]
+pub enum SnapshotError {
+    /// Reading failed, or the input ended too early, or a key could not be read.
+    Io(io::Error),
+    /// The input does not start with the magic bytes of a snapshot.
+    NotASnapshot,
+    /// The snapshot has a version that this version of the crate cannot read.
+    UnsupportedVersion(u8),
+    /// The snapshot is corrupt: the hash does not match, or the nodes do not form a valid
+    /// Fibonacci heap.
+    Corrupt(&'static str),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SnapshotError::Io(error) => write!(f, "could not read snapshot: {error}"),
+            SnapshotError::NotASnapshot => write!(f, "not a Fibonacci heap snapshot"),
+            SnapshotError::UnsupportedVersion(version) => {
+                write!(f, "unsupported snapshot version {version}")
+            }
+            SnapshotError::Corrupt(message) => write!(f, "corrupt snapshot: {message}"),
+        }
+    }
+}
+
+impl Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SnapshotError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SnapshotError {
+    fn from(error: io::Error) -> SnapshotError {
+        SnapshotError::Io(error)
+    }
+}
+
+impl<T: Ord + SnapshotKey> FibonacciHeap<T> {
+    /// Writes a snapshot of the exact shape of the heap to `writer`, which can be restored with
+    /// [read_snapshot](FibonacciHeap::read_snapshot). Unlike the element sequence of most
+    /// serialization formats, the snapshot keeps the trees and marks of the heap, and so its
+    /// amortized state.
+    ///
+    /// A snapshot consists of:
+    ///
+    /// - the magic bytes `FIBH`, and a version byte (currently 1);
+    /// - the number of nodes, as a varint;
+    /// - every node in preorder (the roots from the minimum onwards, and every node directly
+    ///   followed by its children and their subtrees), as a varint `degree << 1 | mark`, followed
+    ///   by the key as written by [SnapshotKey::write_key];
+    /// - a 64-bit FNV-1a hash of everything before it, in little endian.
+    ///
+    /// Varints are unsigned LEB128: 7 bits per byte, least significant group first, with the high
+    /// bit set on every byte but the last.
+    ///
+    /// The writer is not buffered, so you may want to wrap it in a [BufWriter](io::BufWriter).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fibonacci_heap_rust::FibonacciHeap;
+    ///
+    /// let mut heap = FibonacciHeap::from(vec![5u32, 3, 8, 1]);
+    /// heap.pop();
+    /// let mut snapshot = vec![];
+    /// heap.write_snapshot(&mut snapshot).unwrap();
+    ///
+    /// let restored = FibonacciHeap::<u32>::read_snapshot(&snapshot[..]).unwrap();
+    /// assert_eq!(Vec::from(restored), vec![3, 5, 8]);
+    /// ```
+    pub fn write_snapshot(&self, writer: impl Write) -> io::Result<()> {
+        let mut writer = HashingWriter {
+            inner: writer,
+            hash: Fnv1a::new(),
+        };
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&[VERSION])?;
+        write_varint(&mut writer, self.len() as u64)?;
+        let mut result = Ok(());
+        self.for_each_in_preorder(|key, degree, mark| {
+            if result.is_ok() {
+                result = write_varint(&mut writer, (degree as u64) << 1 | mark as u64)
+                    .and_then(|()| key.write_key(&mut writer));
+            }
+        });
+        result?;
+        let hash = writer.hash.0;
+        writer.inner.write_all(&hash.to_le_bytes())
+    }
+
+    /// Reads a snapshot that was written by [write_snapshot](FibonacciHeap::write_snapshot), and
+    /// restores the heap with exactly the same shape.
+    ///
+    /// The snapshot is validated before the heap is built: if it is corrupt in any way, an error is
+    /// returned, and never an invalid heap. Reading stops right after the snapshot, so more data
+    /// may follow it in `reader`.
+    pub fn read_snapshot(reader: impl Read) -> Result<FibonacciHeap<T>, SnapshotError> {
+        Ok(FibonacciHeap::read_snapshot_with_handles(reader)?.0)
+    }
+
+    /// Like [read_snapshot](FibonacciHeap::read_snapshot), but also returns a handle to every node,
+    /// in the order in which the nodes appear in the snapshot (which is the preorder described in
+    /// [write_snapshot](FibonacciHeap::write_snapshot)).
+    pub fn read_snapshot_with_handles(
+        reader: impl Read,
+    ) -> Result<HeapWithHandles<T>, SnapshotError> {
+        let mut reader = HashingReader {
+            inner: reader,
+            hash: Fnv1a::new(),
+        };
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            return Err(SnapshotError::NotASnapshot);
+        }
+        let version = u8::read_key(&mut reader)?;
+        if version != VERSION {
+            return Err(SnapshotError::UnsupportedVersion(version));
+        }
+        let n = read_varint(&mut reader)?;
+        // don't trust `n` for the allocation, the input may be corrupt
+        let mut nodes = Vec::with_capacity(n.min(4096) as usize);
+        for _ in 0..n {
+            let degree_and_mark = read_varint(&mut reader)?;
+            let key = T::read_key(&mut reader)?;
+            let degree = usize::try_from(degree_and_mark >> 1)
+                .map_err(|_| SnapshotError::Corrupt("degree too large"))?;
+            nodes.push((key, degree, degree_and_mark & 1 == 1));
+        }
+        let hash = reader.hash.0;
+        let mut expected_hash = [0; 8];
+        reader.inner.read_exact(&mut expected_hash)?;
+        if hash != u64::from_le_bytes(expected_hash) {
+            return Err(SnapshotError::Corrupt("hash mismatch"));
+        }
+        FibonacciHeap::from_preorder(nodes).map_err(SnapshotError::Corrupt)
+    }
+}
+
+fn invalid_data(error: impl Into<Box<dyn Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+fn write_varint(writer: &mut dyn Write, mut value: u64) -> io::Result<()> {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            return writer.write_all(&[byte]);
+        }
+        writer.write_all(&[byte | 0x80])?;
+    }
+}
+
+fn read_varint(reader: &mut dyn Read) -> io::Result<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = u8::read_key(reader)?;
+        let bits = u64::from(byte & 0x7f);
+        if bits << shift >> shift != bits {
+            return Err(invalid_data("varint too large"));
+        }
+        value |= bits << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(invalid_data("varint too long"))
+}
+
+/// The 64-bit FNV-1a hash function.
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Fnv1a {
+        Fnv1a(0xcbf29ce484222325)
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+}
+
+/// A writer that hashes everything that is written through it.
+struct HashingWriter<W> {
+    inner: W,
+    hash: Fnv1a,
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hash.update(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// A reader that hashes everything that is read through it.
+struct HashingReader<R> {
+    inner: R,
+    hash: Fnv1a,
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.hash.update(&buf[..read]);
+        Ok(read)
+    }
+}
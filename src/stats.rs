@@ -0,0 +1,187 @@
+//! Counters of the work done by a [FibonacciHeap](crate::FibonacciHeap), for tuning workloads.
+//!
+//! The heap always calls into a [Recorder], but without the `stats` feature that is an empty struct
+//! whose methods do nothing, so the counters cost nothing unless they are enabled.
+
+#[cfg(feature = "stats")]
+use crate::FibonacciHeap;
+
+/// Statistics about the operations on a [FibonacciHeap](crate::FibonacciHeap), as returned by
+/// [stats](crate::FibonacciHeap::stats) with the `stats` feature enabled.
+///
+/// The counters (`links` up to `max_degree`) count what happened since the heap was created or
+/// since the last [reset_stats](crate::FibonacciHeap::reset_stats); `roots` and `marked` describe
+/// the current shape of the heap.
+///
+/// # Examples
+///
+/// ```
+/// use fibonacci_heap_rust::FibonacciHeap;
+///
+/// let mut heap = FibonacciHeap::new();
+/// let handles: Vec<_> = (0..8).map(|i| heap.push(i)).collect();
+/// heap.pop(); // links the 7 other nodes into trees of sizes 4, 2 and 1
+/// assert_eq!(heap.stats().links, 4);
+/// assert_eq!(heap.stats().roots, 3);
+///
+/// heap.decrease_key(&handles[7], -1); // cuts 7 from 6, and marks 6
+/// let stats = heap.stats();
+/// assert_eq!((stats.cuts, stats.roots, stats.marked), (1, 4, 1));
+/// assert_eq!(stats.potential(), 6);
+/// ```
+#[cfg(feature = "stats")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct HeapStats {
+    /// The number of times that one root was made a child of another root, while consolidating
+    /// in `pop` or in [`consolidate_step`](crate::FibonacciHeap::consolidate_step) (which `push`,
+    /// `decrease_key` and `from_meld` also call, with a nonzero consolidation budget).
+    pub links: u64,
+    /// The number of nodes that were cut from their parents by `decrease_key` or `delete`,
+    /// including the cascading cuts.
+    pub cuts: u64,
+    /// The number of cuts of marked nodes that had lost a second child (a subset of `cuts`).
+    pub cascading_cuts: u64,
+    /// The number of times that the root list was consolidated (once by every `pop` that leaves
+    /// the heap nonempty).
+    pub consolidations: u64,
+    /// The sum of the lengths of the root list right before every consolidation. Divide by
+    /// `consolidations` for the average length.
+    pub consolidated_roots: u64,
+    /// The longest root list that was consolidated.
+    pub max_consolidated_roots: usize,
+    /// The largest degree that any node has had, whether it got there by links in `pop` or
+    /// `consolidate_step`, or already had it when the heap was built from a snapshot or forest.
+    pub max_degree: usize,
+    /// The current number of trees in the heap.
+    pub roots: usize,
+    /// The current number of marked nodes in the heap.
+    pub marked: usize,
+}
+
+#[cfg(feature = "stats")]
+impl HeapStats {
+    /// Returns the current value of the potential function that is used in the amortized
+    /// analysis of Fibonacci heaps: the number of trees plus twice the number of marked nodes.
+    pub fn potential(&self) -> usize {
+        self.roots + 2 * self.marked
+    }
+}
+
+#[cfg(feature = "stats")]
+impl<T> FibonacciHeap<T> {
+    /// Returns the statistics of the heap: how much work its operations did, and the current
+    /// value of the potential function.
+    ///
+    /// When two heaps are melded, the counters of the resulting heap are those of the heap that
+    /// the elements were moved into (the first argument of `from_meld`).
+    ///
+    /// This is only available with the `stats` feature enabled. Without it, no statistics are
+    /// kept at all.
+    pub fn stats(&self) -> HeapStats {
+        self.stats.0
+    }
+
+    /// Resets the counters of the [stats](FibonacciHeap::stats) to zero, for example after
+    /// warming up. `roots` and `marked` describe the current shape of the heap, so they are not
+    /// reset.
+    pub fn reset_stats(&mut self) {
+        self.stats.0 = HeapStats {
+            roots: self.stats.0.roots,
+            marked: self.stats.0.marked,
+            ..HeapStats::default()
+        };
+    }
+}
+
+/// Keeps the [HeapStats] of a heap up to date, if the `stats` feature is enabled.
+#[cfg(feature = "stats")]
+pub(crate) struct Recorder(HeapStats);
+
+#[cfg(not(feature = "stats"))]
+pub(crate) struct Recorder;
+
+#[cfg(feature = "stats")]
+impl Recorder {
+    pub(crate) const fn new() -> Recorder {
+        Recorder(HeapStats {
+            links: 0,
+            cuts: 0,
+            cascading_cuts: 0,
+            consolidations: 0,
+            consolidated_roots: 0,
+            max_consolidated_roots: 0,
+            max_degree: 0,
+            roots: 0,
+            marked: 0,
+        })
+    }
+
+    /// A node with the given degree and mark was added to the heap, as a root or not.
+    pub(crate) fn add(&mut self, is_root: bool, degree: usize, mark: bool) {
+        self.0.roots += is_root as usize;
+        self.0.marked += mark as usize;
+        self.0.max_degree = self.0.max_degree.max(degree);
+    }
+
+    /// The roots of `other` were moved into this heap.
+    pub(crate) fn absorb(&mut self, other: &mut Recorder) {
+        self.0.roots += std::mem::take(&mut other.0.roots);
+        self.0.marked += std::mem::take(&mut other.0.marked);
+    }
+
+    /// A node was cut from its parent, either directly or as a cascading cut.
+    pub(crate) fn cut(&mut self, was_marked: bool, cascading: bool) {
+        self.0.cuts += 1;
+        self.0.cascading_cuts += cascading as u64;
+        self.0.roots += 1;
+        self.0.marked -= was_marked as usize;
+    }
+
+    /// A node that has lost a child was marked.
+    pub(crate) fn mark(&mut self) {
+        self.0.marked += 1;
+    }
+
+    /// A root with the given degree and mark was popped, and its children became roots.
+    pub(crate) fn pop(&mut self, degree: usize, was_marked: bool) {
+        self.0.roots = self.0.roots + degree - 1;
+        self.0.marked -= was_marked as usize;
+    }
+
+    /// The root list is about to be consolidated.
+    pub(crate) fn consolidate(&mut self) {
+        self.0.consolidations += 1;
+        self.0.consolidated_roots += self.0.roots as u64;
+        self.0.max_consolidated_roots = self.0.max_consolidated_roots.max(self.0.roots);
+    }
+
+    /// A root was made a child of another root, which now has degree `degree`.
+    pub(crate) fn link(&mut self, was_marked: bool, degree: usize) {
+        self.0.links += 1;
+        self.0.roots -= 1;
+        self.0.marked -= was_marked as usize;
+        self.0.max_degree = self.0.max_degree.max(degree);
+    }
+}
+
+#[cfg(not(feature = "stats"))]
+impl Recorder {
+    pub(crate) const fn new() -> Recorder {
+        Recorder
+    }
+
+    pub(crate) fn add(&mut self, _is_root: bool, _degree: usize, _mark: bool) {}
+
+    pub(crate) fn absorb(&mut self, _other: &mut Recorder) {}
+
+    pub(crate) fn cut(&mut self, _was_marked: bool, _cascading: bool) {}
+
+    pub(crate) fn mark(&mut self) {}
+
+    pub(crate) fn pop(&mut self, _degree: usize, _was_marked: bool) {}
+
+    pub(crate) fn consolidate(&mut self) {}
+
+    pub(crate) fn link(&mut self, _was_marked: bool, _degree: usize) {}
+}
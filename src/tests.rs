@@ -732,3 +732,155 @@ fn test_delete_10() {
     fh.delete(ptr1);
     assert_eq!(fh.pop(), None);
 }
+
+#[test]
+#[cfg(not(miri))]
+fn test_drop_tree_of_linear_height() {
+    // Builds a single tree that is a path of 1000000 nodes: every round, the current path (whose
+    // root has degree 1) gets a new root on top of it. Dropping the heap afterwards must not
+    // overflow the stack.
+    let mut fh: FibonacciHeap<i64> = FibonacciHeap::new();
+    let mut root_key = 0;
+    fh.push(root_key);
+    for _ in 1..1000000 {
+        fh.push(root_key - 3);
+        fh.push(root_key - 2);
+        let ptr = fh.push(root_key - 1);
+        assert_eq!(fh.pop(), Some(root_key - 3)); // links the two other new nodes onto the path
+        assert_eq!(fh.delete(ptr), Some(root_key - 1)); // cuts one of them off again
+        root_key -= 2;
+    }
+    assert_eq!(fh.len(), 1000000);
+
+    let mut height = 0;
+    let mut node = fh.min;
+    while !node.is_null() {
+        height += 1;
+        node = unsafe { (*node).child };
+    }
+    assert_eq!(height, 1000000);
+}
+
+#[test]
+fn test_max_degree_bound() {
+    // A node of degree k has at least F(k+2) descendants (including itself), where F(i) is the
+    // i-th Fibonacci number. So for every k, a heap with F(k+2) nodes may contain a node of degree
+    // k, and the bound must allow that.
+    let (mut fib, mut fib_next) = (1usize, 2usize); // F(2), F(3)
+    let mut k = 0;
+    loop {
+        assert!(FibonacciHeap::<i32>::max_degree(fib) >= k);
+        assert!(FibonacciHeap::<i32>::max_degree(fib_next - 1) >= k);
+        match fib.checked_add(fib_next) {
+            Some(sum) => (fib, fib_next) = (fib_next, sum),
+            None => break,
+        }
+        k += 1;
+    }
+    assert!(FibonacciHeap::<i32>::max_degree(usize::MAX) >= k);
+}
+
+#[test]
+fn test_degree_buffer_is_reused() {
+    let mut fh: FibonacciHeap<i32> = FibonacciHeap::from(vec![5, 3, 8, 1, 9, 2, 7, 4]);
+    assert_eq!(fh.pop(), Some(1));
+    assert_eq!(fh.pop(), Some(2));
+    let buffer = fh.degrees.as_ptr();
+    fh.push(0);
+    assert_eq!(fh.pop(), Some(0));
+    assert_eq!(fh.pop(), Some(3));
+    assert!(std::ptr::eq(buffer, fh.degrees.as_ptr()));
+}
+
+#[test]
+fn test_decrease_key_after_meld() {
+    let mut fh1: FibonacciHeap<i32> = FibonacciHeap::new();
+    let mut fh2: FibonacciHeap<i32> = FibonacciHeap::new();
+    let ptr1 = fh1.push(10);
+    fh1.push(20);
+    let ptr2 = fh2.push(30);
+    fh2.push(40);
+    let mut fh = FibonacciHeap::from_meld(fh1, fh2);
+    fh.decrease_key(&ptr2, 5);
+    fh.decrease_key(&ptr1, 6);
+    assert_eq!(fh.pop(), Some(5));
+    assert_eq!(fh.pop(), Some(6));
+    assert_eq!(fh.pop(), Some(20));
+    assert_eq!(fh.pop(), Some(40));
+    assert_eq!(fh.pop(), None);
+}
+
+#[test]
+fn test_delete_after_many_melds() {
+    let mut fh: FibonacciHeap<i32> = FibonacciHeap::new();
+    let mut ptrs = vec![];
+    for i in 0..50 {
+        let mut other = FibonacciHeap::new();
+        ptrs.push(other.push(i));
+        fh = if i % 2 == 0 {
+            FibonacciHeap::from_meld(fh, other)
+        } else {
+            FibonacciHeap::from_meld(other, fh)
+        };
+    }
+    for (i, ptr) in ptrs.into_iter().enumerate() {
+        if i % 3 != 0 {
+            assert_eq!(fh.delete(ptr), Some(i as i32));
+        }
+    }
+    assert_eq!(Vec::from(fh), (0..50).step_by(3).collect::<Vec<_>>());
+}
+
+#[test]
+#[should_panic]
+fn test_decrease_key_wrong_heap_after_meld() {
+    let mut fh1: FibonacciHeap<i32> = FibonacciHeap::new();
+    let mut fh2: FibonacciHeap<i32> = FibonacciHeap::new();
+    let mut fh_wrong: FibonacciHeap<i32> = FibonacciHeap::new();
+    fh1.push(10);
+    let ptr = fh2.push(30);
+    fh_wrong.push(20);
+    let _fh = FibonacciHeap::from_meld(fh1, fh2);
+    fh_wrong.decrease_key(&ptr, 5); // wrong heap: panic
+}
+
+#[test]
+fn test_decrease_key_after_move() {
+    fn build() -> (Box<FibonacciHeap<i32>>, crate::NodePtr<i32>) {
+        let mut fh = FibonacciHeap::new();
+        fh.push(1);
+        let ptr = fh.push(3);
+        (Box::new(fh), ptr)
+    }
+    let (mut fh, ptr) = build();
+    fh.decrease_key(&ptr, 0);
+    assert_eq!(fh.pop(), Some(0));
+    assert_eq!(fh.pop(), Some(1));
+    assert_eq!(fh.pop(), None);
+}
+
+mod algorithms;
+mod binomial_heap;
+mod bulk;
+#[cfg(feature = "sync")]
+mod concurrent;
+mod event_queue;
+mod incremental;
+mod merge;
+mod miri;
+#[cfg(not(miri))]
+mod model;
+mod pairing_heap;
+mod panic_safety;
+mod priority_queue;
+mod select;
+#[cfg(feature = "serde")]
+mod serialization;
+mod snapshot;
+#[cfg(feature = "stats")]
+mod stats;
+mod support;
+#[cfg(feature = "sync")]
+mod sync;
+mod timer_queue;
+mod trace;
@@ -0,0 +1,401 @@
+//! Tests of the graph algorithms, mostly against simple but slow reference implementations on
+//! random graphs.
+
+use super::support::Rng;
+use crate::algorithms::{astar, dijkstra, dijkstra_to, prim};
+
+/// A random directed graph with `n` vertices and `m` edges with weights in `0..max_weight`.
+fn random_graph(rng: &mut Rng, n: usize, m: usize, max_weight: usize) -> Vec<Vec<(usize, u32)>> {
+    let mut graph = vec![vec![]; n];
+    for _ in 0..m {
+        let (from, to) = (rng.below(n), rng.below(n));
+        graph[from].push((to, rng.below(max_weight) as u32));
+    }
+    graph
+}
+
+/// Computes the distances from `source` with the Bellman-Ford algorithm.
+fn bellman_ford(graph: &[Vec<(usize, u32)>], source: usize) -> Vec<Option<u32>> {
+    let mut distances = vec![None; graph.len()];
+    distances[source] = Some(0);
+    for _ in 0..graph.len() {
+        for (from, edges) in graph.iter().enumerate() {
+            let Some(distance) = distances[from] else {
+                continue;
+            };
+            for &(to, weight) in edges {
+                if distances[to].is_none_or(|old| distance + weight < old) {
+                    distances[to] = Some(distance + weight);
+                }
+            }
+        }
+    }
+    distances
+}
+
+#[test]
+fn test_dijkstra_example() {
+    let graph = vec![
+        vec![(1, 7), (2, 9), (5, 14)],
+        vec![(0, 7), (2, 10), (3, 15)],
+        vec![(0, 9), (1, 10), (3, 11), (5, 2)],
+        vec![(1, 15), (2, 11), (4, 6)],
+        vec![(3, 6), (5, 9)],
+        vec![(0, 14), (2, 2), (4, 9)],
+        vec![],
+    ];
+    let paths = dijkstra(&graph, 0);
+    let expected = vec![
+        Some(0),
+        Some(7),
+        Some(9),
+        Some(20),
+        Some(20),
+        Some(11),
+        None,
+    ];
+    assert_eq!(paths.distances, expected);
+    assert_eq!(paths.path_to(4), Some(vec![0, 2, 5, 4]));
+    assert_eq!(paths.path_to(0), Some(vec![0]));
+    assert_eq!(paths.path_to(6), None);
+    assert_eq!(paths.predecessors[0], None);
+    assert_eq!(paths.predecessors[6], None);
+}
+
+#[test]
+fn test_dijkstra_random_graphs() {
+    let mut rng = Rng(42);
+    for _ in 0..30 {
+        let n = rng.below(40) + 1;
+        let m = rng.below(4 * n);
+        let graph = random_graph(&mut rng, n, m, 20);
+        let source = rng.below(n);
+        let paths = dijkstra(&graph, source);
+        assert_eq!(paths.distances, bellman_ford(&graph, source));
+        // every predecessor tree edge must be tight
+        for target in 0..n {
+            let Some(path) = paths.path_to(target) else {
+                continue;
+            };
+            assert_eq!(path[0], source);
+            let length = path
+                .windows(2)
+                .map(|edge| {
+                    let edges = graph[edge[0]].iter().filter(|(to, _)| *to == edge[1]);
+                    edges.map(|&(_, weight)| weight).min().unwrap()
+                })
+                .sum::<u32>();
+            assert_eq!(Some(length), paths.distances[target]);
+        }
+    }
+}
+
+#[test]
+fn test_dijkstra_to_stops_early() {
+    // a path 0 -> 1 -> ... -> 99
+    let graph: Vec<_> = (0..100)
+        .map(|i| if i < 99 { vec![(i + 1, 1u8)] } else { vec![] })
+        .collect();
+    let paths = dijkstra_to(&graph, 0, 10);
+    assert_eq!(paths.distances[10], Some(10));
+    assert_eq!(paths.path_to(10), Some((0..=10).collect()));
+    assert!(paths.distances[11..].iter().all(Option::is_none));
+    assert!(paths.predecessors[11..].iter().all(Option::is_none));
+}
+
+#[test]
+fn test_dijkstra_to_random_graphs() {
+    let mut rng = Rng(7);
+    for _ in 0..30 {
+        let n = rng.below(40) + 1;
+        let graph = random_graph(&mut rng, n, 3 * n, 20);
+        let (source, target) = (rng.below(n), rng.below(n));
+        let expected = bellman_ford(&graph, source);
+        let paths = dijkstra_to(&graph, source, target);
+        assert_eq!(paths.distances[target], expected[target]);
+        // whatever has a distance, has the right one
+        for (distance, expected) in paths.distances.iter().zip(&expected) {
+            assert!(distance.is_none() || distance == expected);
+        }
+    }
+}
+
+/// An undirected edge `(a, b, weight)`.
+type Edge = (usize, usize, i32);
+
+/// A random undirected graph with `n` vertices and `m` edges with weights in `0..max_weight`, with
+/// every edge listed in both directions. Also returns the list of edges.
+fn random_undirected_graph(
+    rng: &mut Rng,
+    n: usize,
+    m: usize,
+    max_weight: usize,
+) -> (Vec<Vec<(usize, i32)>>, Vec<Edge>) {
+    let mut graph = vec![vec![]; n];
+    let mut edges = vec![];
+    for _ in 0..m {
+        let (a, b) = (rng.below(n), rng.below(n));
+        // negative weights are fine for Prim's algorithm
+        let weight = rng.below(max_weight) as i32 - max_weight as i32 / 2;
+        graph[a].push((b, weight));
+        graph[b].push((a, weight));
+        edges.push((a, b, weight));
+    }
+    (graph, edges)
+}
+
+/// A union-find structure (without union by rank, this is only for testing).
+struct Components(Vec<usize>);
+
+impl Components {
+    fn find(&mut self, mut x: usize) -> usize {
+        while self.0[x] != x {
+            self.0[x] = self.0[self.0[x]];
+            x = self.0[x];
+        }
+        x
+    }
+
+    /// Returns false if `a` and `b` were already in the same component.
+    fn union(&mut self, a: usize, b: usize) -> bool {
+        let (a, b) = (self.find(a), self.find(b));
+        self.0[a] = b;
+        a != b
+    }
+}
+
+/// Returns the total weight of a minimum spanning forest, and the number of its edges, computed
+/// with Kruskal's algorithm.
+fn kruskal(n: usize, mut edges: Vec<Edge>) -> (i32, usize) {
+    edges.sort_by_key(|&(_, _, weight)| weight);
+    let mut components = Components((0..n).collect());
+    let mut total = 0;
+    let mut count = 0;
+    for (a, b, weight) in edges {
+        if components.union(a, b) {
+            total += weight;
+            count += 1;
+        }
+    }
+    (total, count)
+}
+
+#[test]
+fn test_prim_example() {
+    // the example from Wikipedia's article on Prim's algorithm, with one extra isolated vertex
+    let mut graph = vec![vec![]; 5];
+    for (a, b, weight) in [(0, 1, 2), (0, 3, 1), (1, 3, 2), (2, 3, 3)] {
+        graph[a].push((b, weight));
+        graph[b].push((a, weight));
+    }
+    let forest = prim(&graph);
+    assert_eq!(forest.total_weight, 6);
+    assert_eq!(forest.edges, vec![(0, 3, 1), (0, 1, 2), (3, 2, 3)]);
+}
+
+#[test]
+fn test_prim_empty_graph() {
+    let graph: Vec<Vec<(usize, u32)>> = vec![];
+    let forest = prim(&graph);
+    assert!(forest.edges.is_empty());
+    assert_eq!(forest.total_weight, 0);
+}
+
+#[test]
+fn test_prim_random_graphs() {
+    let mut rng = Rng(1234);
+    for _ in 0..50 {
+        let n = rng.below(40) + 1;
+        let m = rng.below(3 * n);
+        let (graph, edges) = random_undirected_graph(&mut rng, n, m, 50);
+        let forest = prim(&graph);
+        let (total, count) = kruskal(n, edges);
+        assert_eq!(forest.total_weight, total);
+        assert_eq!(forest.edges.len(), count);
+        // the edges exist, and form a forest
+        let mut components = Components((0..n).collect());
+        for &(a, b, weight) in &forest.edges {
+            assert!(graph[a].contains(&(b, weight)));
+            assert!(components.union(a, b));
+        }
+        assert_eq!(
+            forest
+                .edges
+                .iter()
+                .map(|&(_, _, weight)| weight)
+                .sum::<i32>(),
+            total
+        );
+    }
+}
+
+/// A grid map, where `#` is a wall and everything else is walkable.
+struct Grid {
+    cells: Vec<Vec<bool>>,
+}
+
+impl Grid {
+    fn parse(map: &str) -> Grid {
+        Grid {
+            cells: map
+                .lines()
+                .map(|line| line.chars().map(|c| c != '#').collect())
+                .collect(),
+        }
+    }
+
+    fn random(rng: &mut Rng, width: usize, height: usize) -> Grid {
+        Grid {
+            cells: (0..height)
+                .map(|_| (0..width).map(|_| rng.below(10) >= 3).collect())
+                .collect(),
+        }
+    }
+
+    fn is_free(&self, (x, y): (usize, usize)) -> bool {
+        self.cells.get(y).and_then(|row| row.get(x)).copied() == Some(true)
+    }
+
+    /// The 8 neighbors of a cell, where straight moves cost 10, and diagonal moves cost 14.
+    fn neighbors(&self, (x, y): (usize, usize)) -> Vec<((usize, usize), u32)> {
+        let mut neighbors = vec![];
+        for (dx, dy) in [
+            (-1, -1),
+            (-1, 0),
+            (-1, 1),
+            (0, -1),
+            (0, 1),
+            (1, -1),
+            (1, 0),
+            (1, 1),
+        ] {
+            let next = (x.wrapping_add_signed(dx), y.wrapping_add_signed(dy));
+            if self.is_free(next) {
+                neighbors.push((next, if dx != 0 && dy != 0 { 14 } else { 10 }));
+            }
+        }
+        neighbors
+    }
+
+    /// The same grid as an adjacency list, with cell `(x, y)` as vertex `y * width + x`.
+    fn to_graph(&self) -> Vec<Vec<(usize, u32)>> {
+        let width = self.cells[0].len();
+        let mut graph = vec![];
+        for y in 0..self.cells.len() {
+            for x in 0..width {
+                graph.push(if self.is_free((x, y)) {
+                    let neighbors = self.neighbors((x, y)).into_iter();
+                    neighbors
+                        .map(|((x, y), weight)| (y * width + x, weight))
+                        .collect()
+                } else {
+                    vec![]
+                });
+            }
+        }
+        graph
+    }
+}
+
+/// The octile distance, which is a consistent heuristic for the 8-neighborhood with costs 10/14.
+fn octile((x1, y1): (usize, usize), (x2, y2): (usize, usize)) -> u32 {
+    let (dx, dy) = (x1.abs_diff(x2) as u32, y1.abs_diff(y2) as u32);
+    10 * dx.max(dy) + 4 * dx.min(dy)
+}
+
+#[test]
+fn test_astar_grid_map() {
+    let grid = Grid::parse(
+        "\
+.....#....
+.###.#.##.
+...#.#..#.
+##.#.##.#.
+...#....#.
+.#######..
+..........",
+    );
+    let (start, goal) = ((0, 0), (9, 0));
+    let (path, cost) = astar(
+        start,
+        goal,
+        |&cell| grid.neighbors(cell),
+        |&cell| octile(cell, goal),
+    )
+    .unwrap();
+    assert_eq!(path.first(), Some(&start));
+    assert_eq!(path.last(), Some(&goal));
+    // the path consists of valid moves, and its cost is right
+    let moves = path.windows(2).map(|step| {
+        let neighbors = grid.neighbors(step[0]);
+        neighbors
+            .into_iter()
+            .find(|&(next, _)| next == step[1])
+            .unwrap()
+            .1
+    });
+    assert_eq!(moves.sum::<u32>(), cost);
+    let distances = dijkstra(grid.to_graph(), 0).distances;
+    assert_eq!(Some(cost), distances[9]);
+}
+
+#[test]
+fn test_astar_unreachable_goal() {
+    let grid = Grid::parse("..#..\n..#..\n..#..");
+    let result = astar((0, 0), (4, 2), |&cell| grid.neighbors(cell), |_| 0);
+    assert_eq!(result, None);
+}
+
+#[test]
+fn test_astar_start_is_goal() {
+    let result = astar(3, 3, |_| vec![(4, 1)], |_| 0);
+    assert_eq!(result, Some((vec![3], 0)));
+}
+
+#[test]
+fn test_astar_random_grids() {
+    let mut rng = Rng(99);
+    for _ in 0..30 {
+        let (width, height) = (rng.below(20) + 1, rng.below(20) + 1);
+        let grid = Grid::random(&mut rng, width, height);
+        let start = (rng.below(width), rng.below(height));
+        let goal = (rng.below(width), rng.below(height));
+        if !grid.is_free(start) {
+            continue;
+        }
+        let expected = dijkstra(grid.to_graph(), start.1 * width + start.0).distances;
+        for heuristic in [octile, |_, _| 0] {
+            let result = astar(
+                start,
+                goal,
+                |&cell| grid.neighbors(cell),
+                |&cell| heuristic(cell, goal),
+            );
+            let cost = result.map(|(_, cost)| cost);
+            assert_eq!(cost, expected[goal.1 * width + goal.0]);
+        }
+    }
+}
+
+/// A graph where the heuristic is admissible but not consistent, so that node 2 has to be expanded
+/// again after a shorter path to it is found: A* expands 0, then 2 (via the edge of length 3), and
+/// only then 1, which gives a path of length 2 to node 2.
+fn inconsistent_example() -> Option<(Vec<usize>, u32)> {
+    let graph: Vec<Vec<(usize, u32)>> =
+        vec![vec![(1, 1), (2, 3)], vec![(2, 1)], vec![(3, 3)], vec![]];
+    let estimates = [0, 4, 0, 0];
+    astar(0, 3, |&node| graph[node].clone(), |&node| estimates[node])
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "not consistent")]
+fn test_astar_inconsistent_heuristic_panics_in_debug() {
+    inconsistent_example();
+}
+
+#[test]
+#[cfg(not(debug_assertions))]
+fn test_astar_inconsistent_heuristic_reopens_nodes() {
+    assert_eq!(inconsistent_example(), Some((vec![0, 1, 2, 3], 5)));
+}
@@ -0,0 +1,64 @@
+//! Tests of the binomial heap, and of its worst-case bounds.
+
+use super::support::{comparisons, Counted};
+use crate::{BinomialHeap, FibonacciHeap};
+
+#[test]
+fn test_conversions() {
+    let heap = BinomialHeap::from([5, 3, 8, 1, 3]);
+    heap.check_invariants();
+    assert_eq!(Vec::from(heap), vec![1, 3, 3, 5, 8]);
+    assert_eq!(Vec::from(BinomialHeap::from(vec![2, 0])), vec![0, 2]);
+    assert_eq!(Vec::from(BinomialHeap::<i32>::new()), Vec::<i32>::new());
+}
+
+#[test]
+fn test_trees_have_distinct_orders() {
+    let mut heap = BinomialHeap::new();
+    for i in 0..1000 {
+        heap.push(i);
+        // one tree for every bit that is set in the number of elements
+        assert_eq!(heap.tree_count(), heap.len().count_ones() as usize);
+    }
+    while heap.pop().is_some() {
+        assert_eq!(heap.tree_count(), heap.len().count_ones() as usize);
+    }
+}
+
+/// The first pop after many pushes makes a Fibonacci heap consolidate all its roots, but no
+/// operation of a binomial heap ever makes more than O(log n) comparisons.
+#[test]
+fn test_worst_case_comparisons() {
+    let n: usize = if cfg!(miri) { 1 << 7 } else { 1 << 12 };
+    let log_n = n.ilog2() as usize;
+
+    let mut fibonacci = FibonacciHeap::new();
+    for i in 0..n {
+        fibonacci.push(Counted(i as u64));
+    }
+    assert!(comparisons(|| fibonacci.pop()) >= n / 2);
+
+    let mut heap = BinomialHeap::new();
+    let mut handles = vec![];
+    for i in 0..n {
+        let key = (i * 7919 % n) as u64 + n as u64;
+        assert!(comparisons(|| handles.push(heap.push(Counted(key)))) <= log_n + 2);
+    }
+    for (i, handle) in handles.iter().enumerate().step_by(3) {
+        assert!(comparisons(|| heap.decrease_key(handle, Counted(i as u64))) <= log_n + 3);
+    }
+    for handle in handles.iter().skip(1).step_by(5) {
+        assert!(comparisons(|| heap.delete(handle.clone())) <= 3 * log_n + 3);
+    }
+    let mut other = BinomialHeap::from(vec![Counted(0); n / 2 - 1]);
+    assert!(comparisons(|| heap.absorb(&mut other)) <= 3 * log_n + 3);
+    heap.check_invariants();
+    let mut prev = 0;
+    while !heap.is_empty() {
+        let mut popped = None;
+        assert!(comparisons(|| popped = heap.pop()) <= 3 * log_n + 3);
+        let popped = popped.unwrap().0;
+        assert!(popped >= prev);
+        prev = popped;
+    }
+}
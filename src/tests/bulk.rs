@@ -0,0 +1,78 @@
+//! Tests of building heaps in bulk.
+
+use super::support::{comparisons, roots, Counted};
+use crate::{FibonacciHeap, NodePtr};
+
+/// Checks that `handles` point to the elements with the given keys, by deleting them.
+fn check_handles(mut heap: FibonacciHeap<Counted>, handles: Vec<NodePtr<Counted>>, keys: &[u64]) {
+    for (handle, &key) in handles.into_iter().zip(keys) {
+        assert_eq!(heap.delete(handle), Some(Counted(key)));
+    }
+    assert!(heap.is_empty());
+}
+
+#[test]
+fn test_heapify() {
+    for n in [0, 1, 2, 3, 7, 8, 100] {
+        let keys: Vec<u64> = (0..n).map(|i| i * 7919 % n).collect();
+        let mut built = None;
+        let heap_comparisons = comparisons(|| {
+            built = Some(FibonacciHeap::heapify(
+                keys.iter().map(|&key| Counted(key)).collect(),
+            ))
+        });
+        let (heap, handles) = built.unwrap();
+        // a comparison for every push but the first, and at most one for every link
+        assert!(heap_comparisons <= (2 * n as usize).saturating_sub(2));
+        heap.check_invariants();
+        assert_eq!(heap.unsettled, 0);
+        assert_eq!(roots(&heap), n.count_ones() as usize);
+        check_handles(heap, handles, &keys);
+    }
+}
+
+#[test]
+fn test_from_sorted_vec() {
+    for n in [0u64, 1, 2, 3, 7, 8, 100] {
+        let (heap, handles) = FibonacciHeap::from_sorted_vec((0..n).map(Counted).collect());
+        heap.check_invariants();
+        assert_eq!(roots(&heap), n.count_ones() as usize);
+        assert_eq!(heap.peek(), (n > 0).then_some(&Counted(0)));
+        check_handles(heap, handles, &(0..n).collect::<Vec<_>>());
+    }
+    // equal elements are sorted too
+    let (heap, _) = FibonacciHeap::from_sorted_vec(vec![1, 1, 2, 2, 2]);
+    heap.check_invariants();
+    assert_eq!(Vec::from(heap), vec![1, 1, 2, 2, 2]);
+}
+
+#[test]
+#[should_panic(expected = "not sorted")]
+fn test_from_sorted_vec_with_unsorted_elements() {
+    FibonacciHeap::from_sorted_vec(vec![1, 3, 2]);
+}
+
+/// The first pop after a bulk build only has to go through O(log n) roots, while the one after
+/// pushing all elements has to go through all of them.
+#[test]
+fn test_first_pop_is_cheap() {
+    let n: u64 = if cfg!(miri) { 200 } else { 10_000 };
+    let log_n = n.ilog2() as usize;
+    let keys = || (0..n).map(|i| Counted(i * 7919 % n));
+
+    let mut pushed = FibonacciHeap::new();
+    keys().for_each(|key| drop(pushed.push(key)));
+    assert!(comparisons(|| pushed.pop()) >= n as usize / 2);
+
+    let mut heapified = FibonacciHeap::heapify(keys().collect()).0;
+    assert!(comparisons(|| heapified.pop()) <= 3 * log_n);
+    let mut converted = FibonacciHeap::from(keys().collect::<Vec<_>>());
+    assert!(comparisons(|| converted.pop()) <= 3 * log_n);
+    let mut sorted = FibonacciHeap::from_sorted_vec((0..n).map(Counted).collect()).0;
+    assert!(comparisons(|| sorted.pop()) <= 3 * log_n);
+
+    for heap in [heapified, converted, sorted] {
+        heap.check_invariants();
+        assert_eq!(heap.len(), n as usize - 1);
+    }
+}
@@ -0,0 +1,131 @@
+use std::cell::Cell;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::ConcurrentFibonacciHeap;
+
+#[test]
+fn test_push_and_pop() {
+    let heap = ConcurrentFibonacciHeap::new();
+    heap.push(3);
+    heap.push(1);
+    heap.push(2);
+    assert_eq!(heap.pop(), Some(1));
+    heap.push(0);
+    assert_eq!(heap.pop(), Some(0));
+    assert_eq!(heap.pop(), Some(2));
+    assert_eq!(heap.pop(), Some(3));
+    assert_eq!(heap.pop(), None);
+}
+
+#[test]
+fn test_many_producers() {
+    let heap = Arc::new(ConcurrentFibonacciHeap::new());
+    let producers: Vec<_> = (0..4)
+        .map(|t| {
+            let heap = Arc::clone(&heap);
+            thread::spawn(move || {
+                for i in 0..10 {
+                    heap.push(4 * i + t);
+                }
+            })
+        })
+        .collect();
+    for producer in producers {
+        producer.join().unwrap();
+    }
+    for i in 0..40 {
+        assert_eq!(heap.pop(), Some(i));
+    }
+    assert_eq!(heap.pop(), None);
+}
+
+#[test]
+fn test_pop_wait_for_producers() {
+    let heap = Arc::new(ConcurrentFibonacciHeap::new());
+    let producers: Vec<_> = (0..3)
+        .map(|t| {
+            let heap = Arc::clone(&heap);
+            thread::spawn(move || {
+                for i in 0..5 {
+                    heap.push(format!("{t}-{i}"));
+                }
+            })
+        })
+        .collect();
+    let mut popped = vec![];
+    while popped.len() < 15 {
+        popped.extend(heap.pop_wait(Duration::from_secs(60)));
+    }
+    for producer in producers {
+        producer.join().unwrap();
+    }
+    popped.sort();
+    let mut expected: Vec<String> = (0..3)
+        .flat_map(|t| (0..5).map(move |i| format!("{t}-{i}")))
+        .collect();
+    expected.sort();
+    assert_eq!(popped, expected);
+    assert_eq!(heap.pop(), None);
+}
+
+#[test]
+fn test_pop_wait_times_out() {
+    let heap: ConcurrentFibonacciHeap<i32> = ConcurrentFibonacciHeap::new();
+    let start = Instant::now();
+    assert_eq!(heap.pop_wait(Duration::from_millis(20)), None);
+    assert!(start.elapsed() >= Duration::from_millis(20));
+    assert_eq!(heap.pop_wait(Duration::ZERO), None);
+}
+
+#[test]
+fn test_pop_wait_is_woken_up() {
+    let heap = Arc::new(ConcurrentFibonacciHeap::new());
+    let consumer = {
+        let heap = Arc::clone(&heap);
+        thread::spawn(move || heap.pop_wait(Duration::MAX))
+    };
+    heap.push(42);
+    assert_eq!(consumer.join().unwrap(), Some(42));
+}
+
+#[test]
+fn test_panicking_comparison_in_pop_wait() {
+    thread_local! {
+        static PANIC: Cell<bool> = const { Cell::new(false) };
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct Key(i32);
+
+    impl Ord for Key {
+        fn cmp(&self, other: &Key) -> std::cmp::Ordering {
+            assert!(
+                !PANIC.get(),
+                "comparing {} and {} panicked",
+                self.0,
+                other.0
+            );
+            self.0.cmp(&other.0)
+        }
+    }
+
+    impl PartialOrd for Key {
+        fn partial_cmp(&self, other: &Key) -> Option<std::cmp::Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    let heap = ConcurrentFibonacciHeap::new();
+    for i in 0..3 {
+        heap.push(Key(i));
+    }
+    PANIC.set(true);
+    assert!(catch_unwind(AssertUnwindSafe(|| heap.pop_wait(Duration::ZERO))).is_err());
+    PANIC.set(false);
+    // the consumer is not waiting anymore, so producers must not think so
+    assert_eq!(heap.waiting(), 0);
+    assert!(heap.pop().is_some());
+}
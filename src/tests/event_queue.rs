@@ -0,0 +1,97 @@
+use std::time::Duration;
+
+use crate::EventQueue;
+
+#[test]
+fn test_fifo_on_ties() {
+    let mut queue = EventQueue::new();
+    for i in 0..10 {
+        queue.schedule(i % 3, i);
+    }
+    let order: Vec<_> = std::iter::from_fn(|| queue.advance()).collect();
+    let expected = vec![
+        (0, 0),
+        (0, 3),
+        (0, 6),
+        (0, 9),
+        (1, 1),
+        (1, 4),
+        (1, 7),
+        (2, 2),
+        (2, 5),
+        (2, 8),
+    ];
+    assert_eq!(order, expected);
+}
+
+#[test]
+fn test_reschedule_earlier() {
+    let mut queue = EventQueue::new();
+    let a = queue.schedule(10, 'a');
+    let b = queue.schedule(5, 'b');
+    queue.schedule(5, 'c');
+    assert!(queue.reschedule_earlier(&a, 5));
+    assert!(!queue.reschedule_earlier(&a, 5)); // not earlier
+    assert!(!queue.reschedule_earlier(&a, 8)); // later
+    assert_eq!(queue.peek_time(), Some(5));
+    // `a` keeps its sequence number, so it comes before `b` and `c`
+    assert_eq!(queue.advance(), Some((5, 'a')));
+    assert_eq!(queue.advance(), Some((5, 'b')));
+    assert!(!queue.reschedule_earlier(&b, 5)); // already happened
+    assert_eq!(queue.advance(), Some((5, 'c')));
+    assert_eq!(queue.advance(), None);
+    assert_eq!(queue.now(), Some(5));
+}
+
+#[test]
+fn test_cancel() {
+    let mut queue = EventQueue::new();
+    let a = queue.schedule(1, "a");
+    let b = queue.schedule(2, "b");
+    queue.schedule(3, "c");
+    assert_eq!(queue.cancel(b.clone()), Some((2, "b")));
+    assert_eq!(queue.cancel(b), None);
+    assert_eq!(queue.len(), 2);
+    assert_eq!(queue.advance(), Some((1, "a")));
+    assert_eq!(queue.cancel(a), None);
+    assert_eq!(queue.advance(), Some((3, "c")));
+    assert!(queue.is_empty());
+}
+
+#[test]
+fn test_simulation_with_durations() {
+    // every event schedules a follow-up event, until the simulation ends after one second
+    let mut queue = EventQueue::new();
+    queue.schedule(Duration::ZERO, 0u32);
+    queue.schedule(Duration::from_millis(250), 100);
+    let mut log = vec![];
+    while let Some((now, id)) = queue.advance() {
+        log.push(id);
+        let next = now + Duration::from_millis(300);
+        if next < Duration::from_secs(1) {
+            queue.schedule(next, id + 1);
+        }
+    }
+    assert_eq!(log, vec![0, 100, 1, 101, 2, 102, 3]);
+}
+
+#[test]
+#[should_panic(expected = "before the current time")]
+fn test_schedule_in_the_past() {
+    let mut queue = EventQueue::new();
+    queue.schedule(5, ());
+    queue.advance();
+    queue.schedule(4, ());
+}
+
+#[test]
+#[should_panic(expected = "another event queue")]
+fn test_handle_of_other_queue() {
+    let mut queue1 = EventQueue::new();
+    let mut queue2 = EventQueue::new();
+    let handle = queue1.schedule(5, ());
+    queue1.advance();
+    queue2.schedule(10, ());
+    // this must not touch the event with the same sequence number in `queue2`
+    queue2.reschedule_earlier(&handle, 1);
+}
@@ -0,0 +1,148 @@
+//! Tests of incremental consolidation.
+
+use std::collections::BTreeSet;
+
+use super::support::{comparisons, roots, Counted, Rng};
+use crate::FibonacciHeap;
+
+#[test]
+fn test_consolidate_step() {
+    let mut heap = FibonacciHeap::new();
+    for i in 0..100 {
+        heap.push(Counted(i));
+    }
+    let mut steps = 0;
+    loop {
+        let mut done = false;
+        assert!(comparisons(|| done = heap.consolidate_step(10)) <= 10);
+        heap.check_invariants();
+        steps += 1;
+        if done {
+            break;
+        }
+    }
+    // 100 roots to look at and 100 - 3 links (100 = 64 + 32 + 4)
+    assert!(steps >= 19);
+    assert_eq!(roots(&heap), 3);
+    assert!(heap.consolidate_step(1));
+    assert_eq!(heap.pop(), Some(Counted(0)));
+    heap.check_invariants();
+}
+
+#[test]
+fn test_consolidate_step_on_consolidated_heap() {
+    let mut heap: FibonacciHeap<i32> = FibonacciHeap::new();
+    assert!(heap.consolidate_step(0));
+    assert!(heap.consolidate_step(10));
+    heap.push(1);
+    assert!(!heap.consolidate_step(0));
+    assert!(heap.consolidate_step(1));
+    heap.push(2);
+    heap.push(0);
+    heap.pop();
+    assert!(heap.consolidate_step(0));
+    assert_eq!(Vec::from(heap), vec![1, 2]);
+}
+
+/// With incremental consolidation, the root list stays short, and so a pop never has to link
+/// many roots, unlike after plain pushes.
+#[test]
+fn test_incremental_consolidation_bounds_pop() {
+    let n: u64 = if cfg!(miri) { 200 } else { 10_000 };
+    let log_n = n.ilog2() as usize;
+    let mut plain = FibonacciHeap::new();
+    let mut incremental = FibonacciHeap::new();
+    incremental.set_consolidation_budget(4);
+    for i in 0..n {
+        let key = i * 7919 % n;
+        plain.push(Counted(key));
+        assert!(comparisons(|| incremental.push(Counted(key))) <= 5);
+        assert!(roots(&incremental) <= (i + 1).ilog2() as usize + 2);
+    }
+    assert_eq!(roots(&plain), n as usize);
+    assert!(comparisons(|| plain.pop()) >= n as usize / 2);
+    assert!(comparisons(|| incremental.pop()) <= 4 * log_n);
+    incremental.check_invariants();
+    assert_eq!(Vec::from(incremental), Vec::from(plain));
+}
+
+/// Runs a pseudorandom workload with melds on heaps with incremental consolidation, and compares
+/// them with a model.
+#[test]
+fn test_incremental_consolidation_against_model() {
+    let mut rng = Rng(7);
+    let mut heaps: Vec<FibonacciHeap<(u32, usize)>> =
+        (0..3).map(|_| FibonacciHeap::new()).collect();
+    for (budget, heap) in heaps.iter_mut().enumerate() {
+        heap.set_consolidation_budget(budget);
+    }
+    let mut models: Vec<BTreeSet<(u32, usize)>> = vec![BTreeSet::new(); 3];
+    let mut handles = vec![vec![]; 3];
+    for id in 0..if cfg!(miri) { 300 } else { 5000 } {
+        let h = rng.below(3);
+        match rng.below(10) {
+            0..=3 => {
+                let key = rng.below(1000) as u32;
+                handles[h].push((heaps[h].push((key, id)), key, id));
+                models[h].insert((key, id));
+            }
+            4 => assert_eq!(heaps[h].pop(), models[h].pop_first()),
+            5..=7 if !handles[h].is_empty() => {
+                let i = rng.below(handles[h].len());
+                let (handle, key, id) = &mut handles[h][i];
+                let new_key = key.saturating_sub(rng.below(300) as u32);
+                heaps[h].decrease_key(handle, (new_key, *id));
+                if models[h].remove(&(*key, *id)) {
+                    models[h].insert((new_key, *id));
+                    *key = new_key;
+                }
+            }
+            8 if !handles[h].is_empty() => {
+                let i = rng.below(handles[h].len());
+                let (handle, key, id) = handles[h].swap_remove(i);
+                assert_eq!(
+                    heaps[h].delete(handle).is_some(),
+                    models[h].remove(&(key, id))
+                );
+            }
+            9 => {
+                let other = (h + 1 + rng.below(2)) % 3;
+                let (into, from) = (
+                    std::mem::take(&mut heaps[h]),
+                    std::mem::take(&mut heaps[other]),
+                );
+                let budget = into.consolidation_budget();
+                heaps[h] = FibonacciHeap::from_meld(into, from);
+                assert_eq!(heaps[h].consolidation_budget(), budget);
+                heaps[other].set_consolidation_budget(other);
+                let model = std::mem::take(&mut models[other]);
+                models[h].extend(model);
+                let moved = std::mem::take(&mut handles[other]);
+                handles[h].extend(moved);
+            }
+            _ => {}
+        }
+        for (heap, model) in heaps.iter().zip(&models) {
+            heap.check_invariants();
+            assert_eq!(heap.peek(), model.first());
+            assert_eq!(heap.len(), model.len());
+        }
+    }
+    for (heap, model) in heaps.into_iter().zip(models) {
+        assert_eq!(Vec::from(heap), model.into_iter().collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn test_budget_stays_with_the_heap() {
+    let mut heap1: FibonacciHeap<i32> = FibonacciHeap::new();
+    heap1.set_consolidation_budget(3);
+    let heap2 = FibonacciHeap::from([1, 2, 3]);
+    let heap = FibonacciHeap::from_meld(heap1, heap2);
+    assert_eq!(heap.consolidation_budget(), 3);
+
+    let mut heap2 = FibonacciHeap::from([1, 2, 3]);
+    heap2.set_consolidation_budget(5);
+    let heap = FibonacciHeap::from_meld(FibonacciHeap::new(), heap2);
+    assert_eq!(heap.consolidation_budget(), 0);
+}
@@ -0,0 +1,99 @@
+use std::cmp::Ordering;
+
+use crate::merge_sorted;
+
+#[test]
+fn test_merge_sorted() {
+    let merged: Vec<i32> =
+        merge_sorted(vec![vec![3, 5, 8], vec![1, 9], vec![], vec![2, 4, 6, 7]]).collect();
+    assert_eq!(merged, (1..=9).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_merge_sorted_no_iterators() {
+    let iters: Vec<Vec<u8>> = vec![];
+    assert_eq!(merge_sorted(iters).next(), None);
+}
+
+#[test]
+fn test_merge_sorted_many_random_iterators() {
+    let mut state = 12345u64;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state % 1000
+    };
+    let mut iters: Vec<Vec<u64>> = (0..100)
+        .map(|_| (0..next() % 50).map(|_| next()).collect())
+        .collect();
+    for iter in &mut iters {
+        iter.sort();
+    }
+    let mut expected: Vec<u64> = iters.concat();
+    expected.sort();
+    let merged = merge_sorted(iters.clone());
+    assert_eq!(merged.size_hint(), (expected.len(), Some(expected.len())));
+    assert_eq!(merged.collect::<Vec<_>>(), expected);
+}
+
+/// An element that is compared only by its key, and remembers where it came from.
+#[derive(Debug)]
+struct Entry {
+    key: u8,
+    source: usize,
+    position: usize,
+}
+
+impl Ord for Entry {
+    fn cmp(&self, other: &Entry) -> Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Entry) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Entry {
+    fn eq(&self, other: &Entry) -> bool {
+        self.key == other.key
+    }
+}
+
+impl Eq for Entry {}
+
+#[test]
+fn test_merge_sorted_is_stable() {
+    let keys = [vec![1, 1, 2, 3], vec![1, 2, 2], vec![0, 1, 3, 3]];
+    let iters = keys.iter().enumerate().map(|(source, keys)| {
+        keys.iter().enumerate().map(move |(position, &key)| Entry {
+            key,
+            source,
+            position,
+        })
+    });
+    let merged: Vec<_> = merge_sorted(iters)
+        .map(|entry| (entry.key, entry.source, entry.position))
+        .collect();
+    let mut expected = merged.clone();
+    expected.sort(); // by key, then by source, then by position within the source
+    assert_eq!(merged, expected);
+    assert_eq!(merged.len(), 11);
+}
+
+#[test]
+fn test_merge_sorted_is_lazy() {
+    let mut pulled = 0;
+    let counting = (0..100).inspect(|_| pulled += 1);
+    let mut merged = merge_sorted([
+        Box::new(counting) as Box<dyn Iterator<Item = i32>>,
+        Box::new(50..60),
+    ]);
+    assert_eq!(merged.next(), Some(0));
+    assert_eq!(merged.next(), Some(1));
+    drop(merged);
+    assert_eq!(pulled, 3);
+}
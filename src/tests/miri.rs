@@ -0,0 +1,204 @@
+//! Reduced-size tests that exercise all of the unsafe code (push, pop, decrease_key, delete,
+//! from_meld and drop), meant to be run under Miri:
+//!
+//! ```sh
+//! cargo +nightly miri test --lib miri::
+//! MIRIFLAGS=-Zmiri-tree-borrows cargo +nightly miri test --lib miri::
+//! ```
+//!
+//! The keys are `(value, id)` pairs, where `id` is unique for every pushed element, so that we
+//! always know exactly which node was popped.
+
+use super::support::Rng;
+use crate::{FibonacciHeap, NodePtr};
+
+/// A heap together with a model of what it should contain.
+struct Checked {
+    heap: FibonacciHeap<(i64, usize)>,
+    /// The keys that should be in the heap, sorted.
+    model: Vec<(i64, usize)>,
+    /// The handles of all elements ever pushed into this heap.
+    handles: Vec<NodePtr<(i64, usize)>>,
+}
+
+impl Checked {
+    fn new() -> Checked {
+        Checked {
+            heap: FibonacciHeap::new(),
+            model: vec![],
+            handles: vec![],
+        }
+    }
+
+    fn push(&mut self, key: (i64, usize)) {
+        self.handles.push(self.heap.push(key));
+        let pos = self.model.binary_search(&key).unwrap_err();
+        self.model.insert(pos, key);
+    }
+
+    fn pop(&mut self) {
+        let expected = if self.model.is_empty() {
+            None
+        } else {
+            Some(self.model.remove(0))
+        };
+        assert_eq!(self.heap.pop(), expected);
+        assert_eq!(self.heap.len(), self.model.len());
+    }
+
+    fn decrease_key(&mut self, handle: usize, value: i64) {
+        let handle = &self.handles[handle];
+        let Some(pos) = self.position_of(handle) else {
+            self.heap.decrease_key(handle, (value, 0)); // already gone: nothing happens
+            return;
+        };
+        let old = self.model[pos];
+        let new = (value, old.1);
+        self.heap.decrease_key(handle, new);
+        if new < old {
+            self.model.remove(pos);
+            let pos = self.model.binary_search(&new).unwrap_err();
+            self.model.insert(pos, new);
+        }
+    }
+
+    fn delete(&mut self, handle: usize) {
+        let handle = self.handles[handle].clone();
+        let expected = self.position_of(&handle).map(|pos| self.model.remove(pos));
+        assert_eq!(self.heap.delete(handle), expected);
+        assert_eq!(self.heap.len(), self.model.len());
+    }
+
+    /// Returns the position in the model of the element that `handle` points to, if it is still in
+    /// the heap.
+    fn position_of(&self, handle: &NodePtr<(i64, usize)>) -> Option<usize> {
+        let elem = handle.0.borrow();
+        if elem.invalidated {
+            return None;
+        }
+        let key = unsafe { (*elem.ptr).key };
+        Some(self.model.binary_search(&key).unwrap())
+    }
+
+    fn meld(self, other: Checked) -> Checked {
+        let mut model = self.model;
+        model.extend(other.model);
+        model.sort();
+        let mut handles = self.handles;
+        handles.extend(other.handles);
+        Checked {
+            heap: FibonacciHeap::from_meld(self.heap, other.heap),
+            model,
+            handles,
+        }
+    }
+
+    fn pop_all(mut self) {
+        while !self.model.is_empty() {
+            self.pop();
+        }
+        self.pop();
+    }
+}
+
+/// Runs a random sequence of `ops` operations on two heaps, which are melded now and then.
+fn random_operations(seed: u64, ops: usize) {
+    let mut rng = Rng(seed);
+    let mut heaps = [Checked::new(), Checked::new()];
+    let mut next_id = 0;
+    for _ in 0..ops {
+        let heap = &mut heaps[rng.below(2)];
+        match rng.below(20) {
+            0..=6 => {
+                heap.push((rng.below(100) as i64, next_id));
+                next_id += 1;
+            }
+            7..=10 => heap.pop(),
+            11..=15 if !heap.handles.is_empty() => {
+                let handle = rng.below(heap.handles.len());
+                heap.decrease_key(handle, rng.below(100) as i64 - 50);
+            }
+            16..=18 if !heap.handles.is_empty() => heap.delete(rng.below(heap.handles.len())),
+            19 => {
+                let [heap1, heap2] = heaps;
+                heaps = [heap1.meld(heap2), Checked::new()];
+            }
+            _ => {}
+        }
+    }
+    // the second heap is dropped while it may still contain elements
+    let [heap1, _] = heaps;
+    heap1.pop_all();
+}
+
+#[test]
+fn test_random_operations() {
+    for seed in 1..=8 {
+        random_operations(seed, 300);
+    }
+}
+
+#[test]
+fn test_cascading_cuts() {
+    let mut heap = Checked::new();
+    for i in 0..33 {
+        heap.push((i, i as usize));
+    }
+    heap.pop(); // consolidates the other 32 elements into a single tree
+    for i in (1..33).rev() {
+        heap.decrease_key(i - 1, -(i as i64));
+    }
+    heap.pop_all();
+}
+
+#[test]
+fn test_drop_tree_of_linear_height() {
+    let mut heap = FibonacciHeap::new();
+    let mut handles = vec![heap.push(0)];
+    let mut root_key = 0;
+    for _ in 1..100 {
+        heap.push(root_key - 3);
+        handles.push(heap.push(root_key - 2));
+        let ptr = heap.push(root_key - 1);
+        assert_eq!(heap.pop(), Some(root_key - 3));
+        assert_eq!(heap.delete(ptr), Some(root_key - 1));
+        root_key -= 2;
+    }
+    drop(heap);
+    let mut other = FibonacciHeap::new();
+    for handle in handles {
+        assert_eq!(other.delete(handle), None);
+    }
+}
+
+#[test]
+fn test_handles_outlive_heap() {
+    let mut heap = FibonacciHeap::from(vec!["b".to_string(), "c".to_string()]);
+    let handle = heap.push("a".to_string());
+    let _ = heap.pop(); // consolidates
+    let handle2 = heap.push("d".to_string());
+    drop(heap);
+    let mut other = FibonacciHeap::new();
+    other.decrease_key(&handle, "".to_string());
+    other.decrease_key(&handle2, "".to_string());
+    assert_eq!(other.delete(handle2), None);
+    assert!(other.is_empty());
+}
+
+#[test]
+fn test_meld_heaps_with_trees() {
+    let mut heap1 = Checked::new();
+    let mut heap2 = Checked::new();
+    for i in 0..20 {
+        heap1.push((2 * i, i as usize));
+        heap2.push((2 * i + 1, 20 + i as usize));
+    }
+    heap1.pop();
+    heap2.pop();
+    heap2.decrease_key(5, -1);
+    let mut heap = heap1.meld(heap2);
+    heap.delete(7);
+    heap.delete(27);
+    heap.decrease_key(13, -2);
+    heap.pop_all();
+}
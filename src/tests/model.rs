@@ -0,0 +1,182 @@
+//! Model-based tests: random sequences of operations on a few heaps are applied both to real
+//! `FibonacciHeap`s and to a simple reference model (a `BTreeSet` per heap), and all observable
+//! results are compared. When a sequence fails, proptest shrinks it to a minimal failing one.
+//!
+//! Keys are `(value, id)` pairs with a unique `id` per pushed element, so that the model always
+//! knows exactly which element is popped, even if several elements have the same value.
+
+use std::collections::BTreeSet;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::sample::Index;
+
+use crate::{FibonacciHeap, NodePtr};
+
+/// The number of heaps that the operations are applied to.
+const HEAPS: usize = 3;
+
+type Key = (i16, usize);
+
+#[derive(Debug, Clone)]
+enum Op {
+    Push {
+        heap: usize,
+        value: i16,
+    },
+    Pop {
+        heap: usize,
+    },
+    DecreaseKey {
+        heap: usize,
+        handle: Index,
+        value: i16,
+    },
+    Delete {
+        heap: usize,
+        handle: Index,
+    },
+    Meld {
+        into: usize,
+        from: usize,
+    },
+    DropHeap {
+        heap: usize,
+    },
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    let heap = 0..HEAPS;
+    let value = -50i16..50;
+    prop_oneof![
+        4 => (heap.clone(), value.clone()).prop_map(|(heap, value)| Op::Push { heap, value }),
+        2 => heap.clone().prop_map(|heap| Op::Pop { heap }),
+        3 => (heap.clone(), any::<Index>(), value)
+            .prop_map(|(heap, handle, value)| Op::DecreaseKey { heap, handle, value }),
+        2 => (heap.clone(), any::<Index>()).prop_map(|(heap, handle)| Op::Delete { heap, handle }),
+        1 => (heap.clone(), heap.clone()).prop_map(|(into, from)| Op::Meld { into, from }),
+        1 => heap.prop_map(|heap| Op::DropHeap { heap }),
+    ]
+}
+
+/// The reference model of `HEAPS` heaps.
+#[derive(Default)]
+struct Model {
+    heaps: [BTreeSet<Key>; HEAPS],
+    /// For every element ever pushed: its current key, and the heap it is in (if any).
+    elements: Vec<(Key, Option<usize>)>,
+}
+
+fn run(ops: Vec<Op>) -> Result<(), TestCaseError> {
+    let mut heaps: [FibonacciHeap<Key>; HEAPS] = Default::default();
+    let mut handles: Vec<NodePtr<Key>> = vec![];
+    let mut model = Model::default();
+    for op in ops {
+        match op {
+            Op::Push { heap, value } => {
+                let key = (value, handles.len());
+                handles.push(heaps[heap].push(key));
+                model.heaps[heap].insert(key);
+                model.elements.push((key, Some(heap)));
+            }
+            Op::Pop { heap } => {
+                let expected = model.heaps[heap].pop_first();
+                if let Some((_, id)) = expected {
+                    model.elements[id].1 = None;
+                }
+                prop_assert_eq!(heaps[heap].pop(), expected);
+            }
+            Op::DecreaseKey {
+                heap,
+                handle,
+                value,
+            } if !handles.is_empty() => {
+                let id = handle.index(handles.len());
+                let (old_key, location) = model.elements[id];
+                let new_key = (value, id);
+                let result = catch_unwind(AssertUnwindSafe(|| {
+                    heaps[heap].decrease_key(&handles[id], new_key)
+                }));
+                match location {
+                    Some(location) if location != heap => prop_assert!(result.is_err()),
+                    Some(_) => {
+                        prop_assert!(result.is_ok());
+                        if new_key < old_key {
+                            model.heaps[heap].remove(&old_key);
+                            model.heaps[heap].insert(new_key);
+                            model.elements[id].0 = new_key;
+                        }
+                    }
+                    None => prop_assert!(result.is_ok()),
+                }
+            }
+            Op::Delete { heap, handle } if !handles.is_empty() => {
+                let id = handle.index(handles.len());
+                let (key, location) = model.elements[id];
+                let handle = handles[id].clone();
+                let result = catch_unwind(AssertUnwindSafe(|| heaps[heap].delete(handle)));
+                match location {
+                    Some(location) if location != heap => prop_assert!(result.is_err()),
+                    Some(_) => {
+                        prop_assert_eq!(result.ok(), Some(Some(key)));
+                        model.heaps[heap].remove(&key);
+                        model.elements[id].1 = None;
+                    }
+                    None => prop_assert_eq!(result.ok(), Some(None)),
+                }
+            }
+            Op::Meld { into, from } if into != from => {
+                let melded = FibonacciHeap::from_meld(
+                    std::mem::take(&mut heaps[into]),
+                    std::mem::take(&mut heaps[from]),
+                );
+                heaps[into] = melded;
+                for key in std::mem::take(&mut model.heaps[from]) {
+                    model.elements[key.1].1 = Some(into);
+                    model.heaps[into].insert(key);
+                }
+            }
+            Op::DropHeap { heap } => {
+                heaps[heap] = FibonacciHeap::new();
+                for (_, id) in std::mem::take(&mut model.heaps[heap]) {
+                    model.elements[id].1 = None;
+                }
+            }
+            _ => {}
+        }
+        for (heap, model) in heaps.iter().zip(&model.heaps) {
+            heap.check_invariants();
+            prop_assert_eq!(heap.len(), model.len());
+            prop_assert_eq!(heap.is_empty(), model.is_empty());
+        }
+    }
+    // finally, all heaps must pop all of their elements in the right order
+    for (heap, model) in heaps.into_iter().zip(model.heaps) {
+        prop_assert_eq!(Vec::from(heap), model.into_iter().collect::<Vec<_>>());
+    }
+    Ok(())
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(512))]
+
+    #[test]
+    fn test_against_model(ops in vec(op(), 0..300)) {
+        run(ops)?;
+    }
+}
+
+proptest! {
+    #[test]
+    fn test_push_pop_with_duplicates(values in vec(-5i8..5, 0..500), pops in 0usize..500) {
+        let mut heap = FibonacciHeap::from(values.clone());
+        let mut sorted = values;
+        sorted.sort();
+        for expected in sorted.iter().take(pops) {
+            prop_assert_eq!(heap.pop(), Some(*expected));
+        }
+        heap.check_invariants();
+        prop_assert_eq!(Vec::from(heap), sorted.into_iter().skip(pops).collect::<Vec<_>>());
+    }
+}
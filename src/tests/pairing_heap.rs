@@ -0,0 +1,62 @@
+//! Tests of the pairing heap, and of whether it agrees with the Fibonacci heap.
+
+use super::support::Rng;
+use crate::{FibonacciHeap, PairingHeap};
+
+#[test]
+fn test_conversions() {
+    let heap = PairingHeap::from([5, 3, 8, 1]);
+    assert_eq!(Vec::from(heap), vec![1, 3, 5, 8]);
+    let heap = PairingHeap::from(vec![2, 2, 0]);
+    heap.check_invariants();
+    assert_eq!(Vec::from(heap), vec![0, 2, 2]);
+    assert_eq!(Vec::from(PairingHeap::<i32>::new()), Vec::<i32>::new());
+}
+
+/// Runs the same pseudorandom workload on a pairing heap and a Fibonacci heap, and checks that
+/// every operation returns the same result on both.
+#[test]
+fn test_agrees_with_fibonacci_heap() {
+    let mut rng = Rng(42);
+    let mut pairing = PairingHeap::new();
+    let mut fibonacci = FibonacciHeap::new();
+    let mut handles = vec![];
+    for _ in 0..if cfg!(miri) { 300 } else { 5000 } {
+        match rng.below(8) {
+            0..=2 => {
+                let key = rng.below(10_000) as u64;
+                handles.push((pairing.push(key), fibonacci.push(key)));
+            }
+            3 | 4 => assert_eq!(pairing.pop(), fibonacci.pop()),
+            5 | 6 if !handles.is_empty() => {
+                let (p, f) = &handles[rng.below(handles.len())];
+                let key = rng.below(10_000) as u64;
+                pairing.decrease_key(p, key);
+                fibonacci.decrease_key(f, key);
+            }
+            7 if !handles.is_empty() => {
+                let (p, f) = handles.swap_remove(rng.below(handles.len()));
+                assert_eq!(pairing.delete(p), fibonacci.delete(f));
+            }
+            _ => {}
+        }
+        assert_eq!(pairing.len(), fibonacci.len());
+        assert_eq!(pairing.peek(), fibonacci.peek());
+    }
+    pairing.check_invariants();
+    assert_eq!(Vec::from(pairing), Vec::from(fibonacci));
+}
+
+#[test]
+fn test_from_meld() {
+    let mut heap1 = PairingHeap::from([4, 9]);
+    let nine = heap1.push(9);
+    let mut heap2 = PairingHeap::new();
+    let two = heap2.push(2);
+    heap2.push(7);
+    let mut heap = PairingHeap::from_meld(heap1, heap2);
+    heap.decrease_key(&nine, 1);
+    assert_eq!(heap.delete(two), Some(2));
+    heap.check_invariants();
+    assert_eq!(Vec::from(heap), vec![1, 4, 7, 9]);
+}
@@ -0,0 +1,261 @@
+//! Tests with keys whose `Ord` or `Drop` implementation panics in the middle of an operation. The
+//! heap must stay consistent (and every key must be dropped exactly once) no matter where the panic
+//! happens. These tests are small enough to be run under Miri as well.
+
+use std::cell::Cell;
+use std::cmp::Ordering;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use super::priority_queue::CheckedQueue;
+use crate::{BinomialHeap, FibonacciHeap, IndexedBinaryHeap, NodePtr, PairingHeap};
+
+thread_local! {
+    /// The number of comparisons that may happen before the next one panics.
+    static COMPARISONS_LEFT: Cell<usize> = const { Cell::new(usize::MAX) };
+    /// The value of the key that panics when it is dropped, if any.
+    static PANIC_ON_DROP: Cell<Option<i32>> = const { Cell::new(None) };
+    /// The number of keys that have been created but not dropped yet.
+    static ALIVE: Cell<isize> = const { Cell::new(0) };
+}
+
+#[derive(Debug)]
+struct Key(i32);
+
+impl Key {
+    fn new(value: i32) -> Key {
+        ALIVE.set(ALIVE.get() + 1);
+        Key(value)
+    }
+}
+
+impl Drop for Key {
+    fn drop(&mut self) {
+        ALIVE.set(ALIVE.get() - 1);
+        if PANIC_ON_DROP.get() == Some(self.0) {
+            PANIC_ON_DROP.set(None);
+            panic!("dropping {} panicked", self.0);
+        }
+    }
+}
+
+impl Ord for Key {
+    fn cmp(&self, other: &Key) -> Ordering {
+        match COMPARISONS_LEFT.get() {
+            0 => {
+                COMPARISONS_LEFT.set(usize::MAX);
+                panic!("comparing {} and {} panicked", self.0, other.0);
+            }
+            left => COMPARISONS_LEFT.set(left.saturating_sub(1)),
+        }
+        self.0.cmp(&other.0)
+    }
+}
+
+impl PartialOrd for Key {
+    fn partial_cmp(&self, other: &Key) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Key {
+    fn eq(&self, other: &Key) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Key {}
+
+/// Runs `f` without any comparison panicking.
+fn without_panics<R>(f: impl FnOnce() -> R) -> R {
+    let left = COMPARISONS_LEFT.replace(usize::MAX);
+    let result = f();
+    COMPARISONS_LEFT.set(left);
+    result
+}
+
+/// Runs `f`, and if it panics, checks that `heaps` are still consistent.
+fn attempt(heaps: &mut [&mut FibonacciHeap<Key>], f: impl FnOnce(&mut [&mut FibonacciHeap<Key>])) {
+    if catch_unwind(AssertUnwindSafe(|| f(heaps))).is_err() {
+        without_panics(|| heaps.iter().for_each(|heap| heap.check_structure()));
+    }
+}
+
+/// Pops all elements from `heap` and checks that they come out in order.
+fn drain(mut heap: FibonacciHeap<Key>) {
+    without_panics(|| {
+        let _ = heap.pop(); // may not be the minimum if the last operation panicked
+        heap.check_invariants();
+        let mut prev = i32::MIN;
+        for _ in 0..heap.len() {
+            let popped = heap.pop().unwrap();
+            assert!(popped.0 >= prev);
+            prev = popped.0;
+        }
+        assert!(heap.pop().is_none());
+    });
+}
+
+/// Runs a fixed sequence of operations on heaps with the given consolidation budget, where the
+/// comparison with index `panic_after` panics. Returns the number of comparisons that were made
+/// (if none of them panicked).
+fn workload(panic_after: usize, budget: usize) -> usize {
+    let mut heap = FibonacciHeap::new();
+    let mut other = FibonacciHeap::new();
+    heap.set_consolidation_budget(budget);
+    other.set_consolidation_budget(budget);
+    let mut handles: Vec<NodePtr<Key>> = vec![];
+    COMPARISONS_LEFT.set(panic_after);
+    for i in 0..20 {
+        attempt(&mut [&mut heap], |h| {
+            handles.push(h[0].push(Key::new((i * 7) % 20)))
+        });
+    }
+    attempt(&mut [&mut heap], |h| drop(h[0].pop()));
+    for (i, handle) in handles.iter().rev().step_by(2).enumerate() {
+        attempt(&mut [&mut heap], |h| {
+            h[0].decrease_key(handle, Key::new(-(i as i32)))
+        });
+    }
+    for handle in handles.iter().skip(1).step_by(4) {
+        attempt(&mut [&mut heap], |h| drop(h[0].delete(handle.clone())));
+    }
+    for i in 0..6 {
+        attempt(&mut [&mut other], |h| drop(h[0].push(Key::new(i))));
+    }
+    attempt(&mut [&mut other], |h| drop(h[0].pop()));
+    attempt(&mut [&mut heap, &mut other], |h| {
+        let (heap, other) = h.split_at_mut(1);
+        heap[0].absorb(other[0]);
+    });
+    // if this panics, both heaps have been dropped while unwinding
+    let heap: FibonacciHeap<Key> =
+        catch_unwind(AssertUnwindSafe(|| FibonacciHeap::from_meld(heap, other)))
+            .unwrap_or_default();
+    let comparisons = usize::MAX - COMPARISONS_LEFT.replace(usize::MAX);
+    drain(heap);
+    assert_eq!(ALIVE.get(), 0);
+    comparisons
+}
+
+#[test]
+fn test_panicking_comparisons() {
+    let comparisons = workload(usize::MAX, 0);
+    assert!(comparisons > 50);
+    for panic_after in 0..comparisons {
+        workload(panic_after, 0);
+    }
+}
+
+#[test]
+fn test_panicking_comparisons_with_incremental_consolidation() {
+    let comparisons = workload(usize::MAX, 3);
+    assert!(comparisons > 50);
+    for panic_after in 0..comparisons {
+        workload(panic_after, 3);
+    }
+}
+
+#[test]
+fn test_panicking_comparison_in_decrease_key() {
+    let mut heap = FibonacciHeap::new();
+    let handles: Vec<_> = (0..10).map(|i| heap.push(Key::new(i))).collect();
+    let _ = heap.pop(); // consolidates, so that most nodes have a parent
+    for handle in &handles[1..] {
+        for panic_after in 0..3 {
+            COMPARISONS_LEFT.set(panic_after);
+            let result = catch_unwind(AssertUnwindSafe(|| heap.decrease_key(handle, Key::new(-1))));
+            COMPARISONS_LEFT.set(usize::MAX);
+            if result.is_err() {
+                heap.check_invariants(); // the heap has not been changed at all
+            }
+        }
+    }
+    drain(heap);
+    assert_eq!(ALIVE.get(), 0);
+}
+
+#[test]
+fn test_panicking_drop_of_old_key_in_decrease_key() {
+    let mut heap = FibonacciHeap::new();
+    let handles: Vec<_> = (0..10).map(|i| heap.push(Key::new(i))).collect();
+    let _ = heap.pop();
+    PANIC_ON_DROP.set(Some(7));
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        heap.decrease_key(&handles[7], Key::new(-7))
+    }));
+    assert!(result.is_err());
+    heap.check_invariants();
+    assert_eq!(heap.pop().map(|key| key.0), Some(-7));
+    drain(heap);
+    assert_eq!(ALIVE.get(), 0);
+}
+
+#[test]
+fn test_panicking_drop_while_dropping_heap() {
+    let mut heap = FibonacciHeap::new();
+    let handles: Vec<_> = (0..10).map(|i| heap.push(Key::new(i))).collect();
+    let _ = heap.pop();
+    PANIC_ON_DROP.set(Some(4));
+    assert!(catch_unwind(AssertUnwindSafe(|| drop(heap))).is_err());
+    // the other keys have still been dropped, and all handles are invalidated
+    assert_eq!(ALIVE.get(), 0);
+    let mut other = FibonacciHeap::new();
+    for handle in handles {
+        assert!(other.delete(handle).is_none());
+    }
+}
+
+/// Like [workload], but for any queue, through the
+/// [AddressablePriorityQueue](crate::AddressablePriorityQueue) trait. Since not every queue
+/// restores the heap order after a panic, this only checks that every key is still there.
+fn queue_workload<Q: CheckedQueue<Item = Key>>(panic_after: usize) -> usize {
+    let mut queue = Q::default();
+    let mut handles = vec![];
+    COMPARISONS_LEFT.set(panic_after);
+    let attempt = |queue: &mut Q, f: &mut dyn FnMut(&mut Q)| {
+        if catch_unwind(AssertUnwindSafe(|| f(queue))).is_err() {
+            without_panics(|| queue.check_structure());
+        }
+    };
+    for i in 0..20 {
+        attempt(&mut queue, &mut |q| {
+            handles.push(q.push(Key::new((i * 7) % 20)))
+        });
+    }
+    attempt(&mut queue, &mut |q| drop(q.pop()));
+    for (i, handle) in handles.iter().rev().step_by(2).enumerate() {
+        attempt(&mut queue, &mut |q| {
+            q.decrease_key(handle, Key::new(-(i as i32)))
+        });
+    }
+    for handle in handles.iter().skip(1).step_by(4) {
+        attempt(&mut queue, &mut |q| drop(q.delete(handle.clone())));
+    }
+    let mut other = Q::default();
+    for i in 0..6 {
+        attempt(&mut other, &mut |q| drop(q.push(Key::new(i))));
+    }
+    // if this panics, the elements of `other` are either in `queue` or have been dropped
+    let mut other = Some(other);
+    attempt(&mut queue, &mut |q| q.meld(other.take().unwrap()));
+    let comparisons = usize::MAX - COMPARISONS_LEFT.replace(usize::MAX);
+    let len = queue.len();
+    assert_eq!(std::iter::from_fn(|| queue.pop()).count(), len);
+    drop(queue);
+    assert_eq!(ALIVE.get(), 0);
+    comparisons
+}
+
+#[test]
+fn test_panicking_comparisons_in_other_queues() {
+    fn run<Q: CheckedQueue<Item = Key>>() {
+        let comparisons = queue_workload::<Q>(usize::MAX);
+        assert!(comparisons > 30);
+        for panic_after in 0..comparisons {
+            queue_workload::<Q>(panic_after);
+        }
+    }
+    run::<PairingHeap<Key>>();
+    run::<BinomialHeap<Key>>();
+    run::<IndexedBinaryHeap<Key>>();
+}
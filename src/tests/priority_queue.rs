@@ -0,0 +1,210 @@
+//! Tests that run against every implementation of `AddressablePriorityQueue`.
+
+use std::collections::{BTreeSet, HashMap};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use super::support::Rng;
+use crate::{
+    AddressablePriorityQueue, BinomialHeap, FibonacciHeap, IndexedBinaryHeap, PairingHeap,
+};
+
+/// A queue whose invariants can be checked.
+pub(super) trait CheckedQueue: AddressablePriorityQueue + Default {
+    /// Panics if the queue is not valid.
+    fn check_invariants(&self);
+
+    /// Panics if the queue is not valid, except that its elements may be out of order in the ways
+    /// that are allowed after a comparison panicked.
+    fn check_structure(&self);
+}
+
+impl<T: Ord> CheckedQueue for FibonacciHeap<T> {
+    fn check_invariants(&self) {
+        FibonacciHeap::check_invariants(self)
+    }
+
+    fn check_structure(&self) {
+        FibonacciHeap::check_structure(self)
+    }
+}
+
+impl<T: Ord> CheckedQueue for PairingHeap<T> {
+    fn check_invariants(&self) {
+        PairingHeap::check_invariants(self)
+    }
+
+    fn check_structure(&self) {
+        PairingHeap::check_structure(self)
+    }
+}
+
+impl<T: Ord> CheckedQueue for BinomialHeap<T> {
+    fn check_invariants(&self) {
+        BinomialHeap::check_invariants(self)
+    }
+
+    fn check_structure(&self) {
+        BinomialHeap::check_structure(self)
+    }
+}
+
+impl<T: Ord> CheckedQueue for IndexedBinaryHeap<T> {
+    fn check_invariants(&self) {
+        IndexedBinaryHeap::check_invariants(self)
+    }
+
+    fn check_structure(&self) {
+        IndexedBinaryHeap::check_structure(self)
+    }
+}
+
+/// Runs a pseudorandom sequence of operations on three queues, and compares them with a model.
+/// Elements are `(key, id)` pairs, so that they are all distinct.
+fn against_model<Q: CheckedQueue<Item = (u32, usize)>>(seed: u64, steps: usize) {
+    let mut rng = Rng(seed);
+    let mut queues: Vec<Q> = (0..3).map(|_| Q::default()).collect();
+    let mut models: Vec<BTreeSet<(u32, usize)>> = vec![BTreeSet::new(); 3];
+    // the handles of every queue, by id, including the ones of elements that are gone
+    let mut handles: Vec<HashMap<usize, Q::Handle>> = vec![HashMap::new(); 3];
+    // the current key of every element, by id
+    let mut keys: HashMap<usize, u32> = HashMap::new();
+    for id in 0..steps {
+        let q = rng.below(3);
+        match rng.below(10) {
+            0..=3 => {
+                let key = rng.below(1000) as u32;
+                handles[q].insert(id, queues[q].push((key, id)));
+                models[q].insert((key, id));
+                keys.insert(id, key);
+            }
+            4 | 5 => {
+                let popped = queues[q].pop();
+                assert_eq!(popped, models[q].pop_first());
+            }
+            6 | 7 if !handles[q].is_empty() => {
+                let &other = handles[q].keys().nth(rng.below(handles[q].len())).unwrap();
+                let key = keys[&other];
+                let new_key = key.saturating_sub(rng.below(500) as u32);
+                queues[q].decrease_key(&handles[q][&other], (new_key, other));
+                if models[q].remove(&(key, other)) {
+                    models[q].insert((new_key, other));
+                    keys.insert(other, new_key);
+                }
+            }
+            8 if !handles[q].is_empty() => {
+                let &other = handles[q].keys().nth(rng.below(handles[q].len())).unwrap();
+                let deleted = queues[q].delete(handles[q][&other].clone());
+                let key = keys[&other];
+                assert_eq!(deleted.is_some(), models[q].remove(&(key, other)));
+                if let Some(deleted) = deleted {
+                    assert_eq!(deleted, (key, other));
+                }
+            }
+            9 => {
+                let from = (q + 1 + rng.below(2)) % 3;
+                let other = std::mem::take(&mut queues[from]);
+                queues[q].meld(other);
+                let model = std::mem::take(&mut models[from]);
+                models[q].extend(model);
+                let moved = std::mem::take(&mut handles[from]);
+                handles[q].extend(moved);
+            }
+            _ => {}
+        }
+        for (queue, model) in queues.iter().zip(&models) {
+            queue.check_invariants();
+            assert_eq!(queue.len(), model.len());
+            assert_eq!(queue.is_empty(), model.is_empty());
+            assert_eq!(queue.peek(), model.first());
+        }
+    }
+    for (mut queue, model) in queues.into_iter().zip(models) {
+        let drained: Vec<_> = std::iter::from_fn(|| queue.pop()).collect();
+        assert_eq!(drained, model.into_iter().collect::<Vec<_>>());
+    }
+}
+
+fn foreign_handles<Q: CheckedQueue<Item = i32>>() {
+    let mut queue1 = Q::default();
+    let mut queue2 = Q::default();
+    let handle = queue1.push(1);
+    assert!(catch_unwind(AssertUnwindSafe(|| queue2.decrease_key(&handle, 0))).is_err());
+    queue2.push(2);
+    assert!(catch_unwind(AssertUnwindSafe(|| queue2.delete(handle.clone()))).is_err());
+    // once melded, the handle belongs to the other queue as well
+    queue2.meld(queue1);
+    queue2.decrease_key(&handle, 0);
+    assert_eq!(queue2.pop(), Some(0));
+    // handles of elements that are gone are ignored, by every queue
+    let mut queue3 = Q::default();
+    assert_eq!(queue3.delete(handle.clone()), None);
+    queue3.decrease_key(&handle, -1);
+    assert!(queue3.is_empty());
+}
+
+fn handles_after_drop<Q: CheckedQueue<Item = i32>>() {
+    let mut queue = Q::default();
+    let handles: Vec<_> = (0..10).map(|i| queue.push(i)).collect();
+    queue.pop();
+    drop(queue);
+    let mut other = Q::default();
+    other.push(0);
+    for handle in handles {
+        assert_eq!(other.delete(handle), None);
+    }
+}
+
+/// Builds queues whose trees are very deep, and drops them, which must not overflow the stack.
+fn deep_trees<Q: CheckedQueue<Item = i32>>(n: i32) {
+    let mut queue = Q::default();
+    for i in (0..n).rev() {
+        queue.push(i);
+    }
+    drop(queue);
+
+    let mut queue = Q::default();
+    let handles: Vec<_> = (0..n).map(|i| queue.push(i + n)).collect();
+    queue.pop();
+    for (i, handle) in handles.iter().enumerate().skip(1) {
+        queue.decrease_key(handle, i as i32 - n);
+    }
+    queue.check_invariants();
+    drop(queue);
+}
+
+macro_rules! queue_tests {
+    ($($module:ident: $queue:ident,)*) => {$(
+        mod $module {
+            use super::*;
+
+            #[test]
+            fn test_against_model() {
+                for seed in 1..=if cfg!(miri) { 2 } else { 10 } {
+                    against_model::<$queue<(u32, usize)>>(seed, if cfg!(miri) { 200 } else { 2000 });
+                }
+            }
+
+            #[test]
+            fn test_foreign_handles() {
+                foreign_handles::<$queue<i32>>();
+            }
+
+            #[test]
+            fn test_handles_after_drop() {
+                handles_after_drop::<$queue<i32>>();
+            }
+
+            #[test]
+            fn test_deep_trees() {
+                deep_trees::<$queue<i32>>(if cfg!(miri) { 100 } else { 100_000 });
+            }
+        }
+    )*};
+}
+
+queue_tests! {
+    fibonacci: FibonacciHeap,
+    pairing: PairingHeap,
+    binomial: BinomialHeap,
+    indexed_binary: IndexedBinaryHeap,
+}
@@ -0,0 +1,76 @@
+use std::cmp::Reverse;
+
+use crate::{smallest_k, FibonacciHeap};
+
+#[test]
+fn test_into_smallest_k() {
+    let heap = FibonacciHeap::from(vec![9, 3, 7, 1, 3, 8]);
+    assert_eq!(heap.into_smallest_k(4), vec![1, 3, 3, 7]);
+    let heap = FibonacciHeap::from(vec![2, 1]);
+    assert_eq!(heap.into_smallest_k(5), vec![1, 2]);
+    let heap = FibonacciHeap::from(vec![2, 1]);
+    assert_eq!(heap.into_smallest_k(0), Vec::<i32>::new());
+}
+
+#[test]
+fn test_smallest_k() {
+    let values: Vec<u64> = (0..1000).map(|i| (i * 7919) % 1009).collect();
+    let mut sorted = values.clone();
+    sorted.sort();
+    for k in [0, 1, 10, 999, 1000, 2000] {
+        assert_eq!(smallest_k(values.iter().copied(), k), sorted[..k.min(1000)]);
+    }
+    assert_eq!(smallest_k(Vec::<u8>::new(), 3), Vec::<u8>::new());
+}
+
+#[test]
+fn test_push_bounded_keeps_largest() {
+    let mut heap = FibonacciHeap::new();
+    let mut evicted = vec![];
+    for value in [5, 3, 8, 1, 9, 2, 7] {
+        evicted.extend(heap.push_bounded(value, 3));
+    }
+    assert_eq!(evicted, vec![1, 3, 2, 5]);
+    heap.check_invariants();
+    assert_eq!(Vec::from(heap), vec![7, 8, 9]);
+}
+
+#[test]
+fn test_push_bounded_reverse_keeps_smallest() {
+    let mut heap = FibonacciHeap::new();
+    for value in (0..100).rev() {
+        heap.push_bounded(Reverse(value), 10);
+        assert!(heap.len() <= 10);
+    }
+    let kept: Vec<_> = Vec::from(heap).into_iter().map(|Reverse(v)| v).collect();
+    assert_eq!(kept, (0..10).rev().collect::<Vec<_>>());
+}
+
+#[test]
+fn test_push_bounded_zero_capacity() {
+    let mut heap = FibonacciHeap::new();
+    assert_eq!(heap.push_bounded(1, 0), Some(1));
+    assert!(heap.is_empty());
+}
+
+#[test]
+fn test_push_bounded_over_capacity() {
+    // a heap that already has more elements than the capacity only loses one element per push
+    let mut heap = FibonacciHeap::from(vec![1, 2, 3, 4]);
+    assert_eq!(heap.push_bounded(5, 2), Some(1));
+    assert_eq!(heap.push_bounded(0, 2), Some(0));
+    assert_eq!(heap.len(), 4);
+}
+
+#[test]
+fn test_peek() {
+    let mut heap = FibonacciHeap::new();
+    assert_eq!(heap.peek(), None);
+    let handle = heap.push(5);
+    heap.push(3);
+    assert_eq!(heap.peek(), Some(&3));
+    heap.decrease_key(&handle, 1);
+    assert_eq!(heap.peek(), Some(&1));
+    heap.delete(handle);
+    assert_eq!(heap.peek(), Some(&3));
+}
@@ -0,0 +1,118 @@
+//! Tests of the `serde` feature.
+
+use super::support::{heap_with_trees, preorder};
+use crate::FibonacciHeap;
+
+/// The elements of `heap`, sorted.
+fn elements(heap: &FibonacciHeap<i32>) -> Vec<i32> {
+    let mut elements: Vec<i32> = preorder(heap).into_iter().map(|(key, _, _)| key).collect();
+    elements.sort();
+    elements
+}
+
+fn forest_json(heap: &FibonacciHeap<i32>) -> String {
+    let mut json = vec![];
+    heap.serialize_forest(&mut serde_json::Serializer::new(&mut json))
+        .unwrap();
+    String::from_utf8(json).unwrap()
+}
+
+fn from_forest_json(json: &str) -> Result<FibonacciHeap<i32>, serde_json::Error> {
+    FibonacciHeap::deserialize_forest(&mut serde_json::Deserializer::from_str(json))
+}
+
+#[test]
+fn test_elements_roundtrip() {
+    let heap = heap_with_trees();
+    let elements = elements(&heap);
+    let json = serde_json::to_string(&heap).unwrap();
+    let restored: FibonacciHeap<i32> = serde_json::from_str(&json).unwrap();
+    restored.check_invariants();
+    assert_eq!(Vec::from(restored), elements);
+    assert_eq!(Vec::from(heap), elements);
+}
+
+#[test]
+fn test_empty_heap() {
+    let heap = FibonacciHeap::<u8>::new();
+    assert_eq!(serde_json::to_string(&heap).unwrap(), "[]");
+    assert_eq!(forest_json(&FibonacciHeap::new()), "[]");
+    assert!(from_forest_json("[]").unwrap().is_empty());
+}
+
+#[test]
+fn test_elements_with_handles() {
+    let json = "[5, 9, 7]";
+    let mut deserializer = serde_json::Deserializer::from_str(json);
+    let (mut heap, handles) = FibonacciHeap::deserialize_with_handles(&mut deserializer).unwrap();
+    heap.decrease_key(&handles[1], 1);
+    assert_eq!(heap.delete(handles[2].clone()), Some(7));
+    assert_eq!(Vec::from(heap), vec![1, 5]);
+}
+
+#[test]
+fn test_forest_roundtrip_preserves_shape() {
+    let heap = heap_with_trees();
+    let elements = elements(&heap);
+    let json = forest_json(&heap);
+    assert!(json.contains("true"), "the heap should have marked nodes");
+    let restored = from_forest_json(&json).unwrap();
+    restored.check_invariants();
+    assert_eq!(forest_json(&restored), json);
+    assert_eq!(Vec::from(restored), elements);
+}
+
+#[test]
+fn test_forest_with_handles() {
+    let heap = heap_with_trees();
+    let json = forest_json(&heap);
+    let mut deserializer = serde_json::Deserializer::from_str(&json);
+    let (mut restored, handles) =
+        FibonacciHeap::deserialize_forest_with_handles(&mut deserializer).unwrap();
+    // the handles are in the same order as the nodes in the serialized forest
+    for (handle, (key, _, _)) in handles.into_iter().zip(preorder(&heap)).rev() {
+        assert_eq!(restored.delete(handle), Some(key));
+        restored.check_invariants();
+    }
+    assert!(restored.is_empty());
+}
+
+#[test]
+fn test_forest_validation() {
+    // heap order violated
+    assert!(from_forest_json("[[5,1,false],[4,0,false]]").is_err());
+    // missing children
+    assert!(from_forest_json("[[1,2,false],[4,0,false]]").is_err());
+    // a root of degree 3 must have at least 5 nodes in its tree
+    assert!(from_forest_json("[[0,3,false],[1,0,false],[2,0,false],[3,0,false]]").is_err());
+    // of two leaves under a root, one was linked with degree 1, so it must have lost a child and
+    // be marked
+    assert!(from_forest_json("[[0,2,false],[1,0,false],[2,0,false]]").is_err());
+    assert!(from_forest_json("[[0,2,false],[1,0,false],[2,0,true]]").is_ok());
+    // absurd degrees
+    assert!(from_forest_json("[[0,18446744073709551615,false]]").is_err());
+    // not a forest at all
+    assert!(from_forest_json("[1, 2]").is_err());
+    // but these are fine, and the minimum does not have to come first
+    let heap = from_forest_json("[[3,0,true],[1,1,false],[2,0,true]]").unwrap();
+    heap.check_invariants();
+    assert_eq!(Vec::from(heap), vec![1, 2, 3]);
+    let Err(error) = from_forest_json("[[5,1,false],[4,0,false]]") else {
+        panic!("invalid forest accepted");
+    };
+    assert!(error.to_string().contains("heap order violated"));
+}
+
+/// A root of degree 16 with a long chain and 15 leaves as children has enough nodes for its
+/// degree, but cutting the chain leaves a root whose degree `consolidate` cannot handle.
+#[test]
+fn test_forest_with_too_small_children() {
+    let mut nodes = vec![(0, 16, false)];
+    nodes.extend((1..=2600).map(|key| (key, usize::from(key < 2600), false)));
+    nodes.extend((2601..2616).map(|key| (key, 0, false)));
+    let json = serde_json::to_string(&nodes).unwrap();
+    let Err(error) = from_forest_json(&json) else {
+        panic!("invalid forest accepted");
+    };
+    assert!(error.to_string().contains("children of too small degree"));
+}
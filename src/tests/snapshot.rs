@@ -0,0 +1,214 @@
+//! Tests of the binary snapshot format.
+
+use std::cmp::Reverse;
+
+use super::support::{heap_with_trees, preorder};
+use crate::{FibonacciHeap, SnapshotError};
+
+fn snapshot<T: Ord + crate::SnapshotKey>(heap: &FibonacciHeap<T>) -> Vec<u8> {
+    let mut bytes = vec![];
+    heap.write_snapshot(&mut bytes).unwrap();
+    bytes
+}
+
+/// Appends `value` to `bytes` as a varint.
+fn push_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+/// Builds a snapshot by hand from the number of nodes and the bytes after it, with a correct hash.
+fn snapshot_from_body(n: u64, body: &[u8]) -> Vec<u8> {
+    let mut bytes = b"FIBH\x01".to_vec();
+    push_varint(&mut bytes, n);
+    bytes.extend_from_slice(body);
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for &byte in &bytes {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    bytes.extend_from_slice(&hash.to_le_bytes());
+    bytes
+}
+
+#[test]
+fn test_roundtrip_preserves_shape() {
+    let heap = heap_with_trees();
+    assert!(preorder(&heap).iter().any(|&(_, degree, _)| degree > 1));
+    assert!(preorder(&heap).iter().any(|&(_, _, mark)| mark));
+
+    let bytes = snapshot(&heap);
+    let restored = FibonacciHeap::<i32>::read_snapshot(&bytes[..]).unwrap();
+    restored.check_invariants();
+    assert_eq!(preorder(&restored), preorder(&heap));
+    assert_eq!(Vec::from(restored), Vec::from(heap));
+}
+
+#[test]
+fn test_empty_heap() {
+    let bytes = snapshot(&FibonacciHeap::<u64>::new());
+    assert_eq!(bytes.len(), 4 + 1 + 1 + 8);
+    assert!(FibonacciHeap::<u64>::read_snapshot(&bytes[..])
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_handles() {
+    let heap = heap_with_trees();
+    let nodes = preorder(&heap);
+    let bytes = snapshot(&heap);
+    let (mut restored, handles) =
+        FibonacciHeap::<i32>::read_snapshot_with_handles(&bytes[..]).unwrap();
+    assert_eq!(handles.len(), nodes.len());
+    let (last, _, _) = nodes[nodes.len() - 1];
+    assert_eq!(
+        restored.delete(handles[nodes.len() - 1].clone()),
+        Some(last)
+    );
+    restored.decrease_key(&handles[1], -1000);
+    assert_eq!(restored.pop(), Some(-1000));
+    restored.check_invariants();
+}
+
+#[test]
+fn test_reading_stops_after_the_snapshot() {
+    let mut bytes = snapshot(&FibonacciHeap::from(vec![3u8, 1, 2]));
+    let len = bytes.len();
+    bytes.extend_from_slice(b"rest");
+    let mut reader = &bytes[..];
+    let heap = FibonacciHeap::<u8>::read_snapshot(&mut reader).unwrap();
+    assert_eq!(Vec::from(heap), vec![1, 2, 3]);
+    assert_eq!(reader, b"rest");
+    assert_eq!(bytes.len() - reader.len(), len);
+}
+
+#[test]
+fn test_other_keys() {
+    let mut heap = FibonacciHeap::new();
+    for word in ["pear", "apple", "", "fig", "cherry"] {
+        heap.push((
+            word.to_string(),
+            Reverse(word.len() as u16),
+            word.starts_with('f'),
+        ));
+    }
+    heap.pop();
+    let bytes = snapshot(&heap);
+    let restored =
+        FibonacciHeap::<(String, Reverse<u16>, bool)>::read_snapshot(&bytes[..]).unwrap();
+    assert_eq!(preorder(&restored), preorder(&heap));
+
+    let heap = FibonacciHeap::from(vec!['z', 'ß', '€', 'a']);
+    let restored = FibonacciHeap::<char>::read_snapshot(&snapshot(&heap)[..]).unwrap();
+    assert_eq!(Vec::from(restored), vec!['a', 'z', 'ß', '€']);
+}
+
+#[test]
+fn test_not_a_snapshot() {
+    let result = FibonacciHeap::<i32>::read_snapshot(&b"not a snapshot"[..]);
+    assert!(matches!(result, Err(SnapshotError::NotASnapshot)));
+}
+
+#[test]
+fn test_unsupported_version() {
+    let mut bytes = snapshot(&heap_with_trees());
+    bytes[4] = 2;
+    let result = FibonacciHeap::<i32>::read_snapshot(&bytes[..]);
+    assert!(matches!(result, Err(SnapshotError::UnsupportedVersion(2))));
+}
+
+#[test]
+fn test_truncated_snapshots_are_rejected() {
+    let bytes = snapshot(&heap_with_trees());
+    for len in 0..bytes.len() {
+        let result = FibonacciHeap::<i32>::read_snapshot(&bytes[..len]);
+        assert!(
+            matches!(result, Err(SnapshotError::Io(_))),
+            "a snapshot truncated to {len} bytes was accepted"
+        );
+    }
+}
+
+#[test]
+fn test_corrupt_snapshots_are_rejected() {
+    let bytes = snapshot(&heap_with_trees());
+    let step = if cfg!(miri) { 13 } else { 1 };
+    for i in (0..bytes.len()).step_by(step) {
+        for bit in 0..8 {
+            let mut corrupt = bytes.clone();
+            corrupt[i] ^= 1 << bit;
+            assert!(
+                FibonacciHeap::<i32>::read_snapshot(&corrupt[..]).is_err(),
+                "flipping bit {bit} of byte {i} was not detected"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_invalid_forests_are_rejected() {
+    let cases: [(u64, &[u8], &str); 3] = [
+        // a root with a child that is smaller than it
+        (2, &[2, 5, 0, 0, 0, 0, 1, 0, 0, 0], "heap order violated"),
+        // a root that claims a child, but nothing follows it
+        (1, &[2, 5, 0, 0, 0], "missing children"),
+        // a root of degree 3 with only three nodes below it
+        (
+            4,
+            &[6, 1, 0, 0, 0, 0, 2, 0, 0, 0, 0, 3, 0, 0, 0, 0, 4, 0, 0, 0],
+            "children of too small degree",
+        ),
+    ];
+    for (n, body, message) in cases {
+        let bytes = snapshot_from_body(n, body);
+        match FibonacciHeap::<i32>::read_snapshot(&bytes[..]) {
+            Err(SnapshotError::Corrupt(m)) => assert_eq!(m, message),
+            Err(error) => panic!("unexpected error: {error}"),
+            Ok(_) => panic!("an invalid forest was accepted"),
+        }
+    }
+}
+
+/// A root of degree 16 with a long chain and 15 leaves as children has enough nodes for its
+/// degree, but deleting the chain through the handles used to make `consolidate` index out of
+/// bounds.
+#[test]
+fn test_children_of_too_small_degree_are_rejected() {
+    let mut nodes = vec![(0, 16)];
+    nodes.extend((1..=2600).map(|key| (key, u64::from(key < 2600))));
+    nodes.extend((2601..2616).map(|key| (key, 0)));
+    let mut body = vec![];
+    for (key, degree) in nodes {
+        push_varint(&mut body, degree << 1);
+        body.extend_from_slice(&i32::to_le_bytes(key));
+    }
+    let bytes = snapshot_from_body(2616, &body);
+    match FibonacciHeap::<i32>::read_snapshot_with_handles(&bytes[..]) {
+        Err(SnapshotError::Corrupt(message)) => {
+            assert_eq!(message, "children of too small degree")
+        }
+        Err(error) => panic!("unexpected error: {error}"),
+        Ok(_) => panic!("an invalid forest was accepted"),
+    }
+}
+
+#[test]
+fn test_invalid_keys_are_rejected() {
+    let bytes = snapshot_from_body(1, &[0, 2]);
+    let result = FibonacciHeap::<bool>::read_snapshot(&bytes[..]);
+    assert!(
+        matches!(result, Err(SnapshotError::Io(error)) if error.kind() == std::io::ErrorKind::InvalidData)
+    );
+}
+
+#[test]
+fn test_overlong_varint_is_rejected() {
+    let mut bytes = b"FIBH\x01".to_vec();
+    bytes.extend_from_slice(&[0xff; 11]);
+    let result = FibonacciHeap::<i32>::read_snapshot(&bytes[..]);
+    assert!(matches!(result, Err(SnapshotError::Io(_))));
+}
@@ -0,0 +1,146 @@
+//! Tests of the `stats` feature. The current roots and marks are also checked against the actual
+//! forest by `check_invariants`, so every other test checks those as well.
+
+use crate::{FibonacciHeap, HeapStats};
+
+#[test]
+fn test_new_heap() {
+    let heap = FibonacciHeap::<i32>::new();
+    assert_eq!(heap.stats(), HeapStats::default());
+    assert_eq!(heap.stats().potential(), 0);
+}
+
+#[test]
+fn test_links_and_consolidations() {
+    let mut heap = FibonacciHeap::new();
+    for i in 0..100 {
+        heap.push(i);
+    }
+    assert_eq!(heap.stats().roots, 100);
+    assert_eq!(heap.stats().potential(), 100);
+
+    heap.pop();
+    let stats = heap.stats();
+    // 99 = 0b1100011, so 99 nodes are linked into trees of sizes 64, 32, 2 and 1
+    assert_eq!(stats.roots, 4);
+    assert_eq!(stats.links, 95);
+    assert_eq!(stats.max_degree, 6);
+    assert_eq!(stats.consolidations, 1);
+    assert_eq!(stats.consolidated_roots, 99);
+    assert_eq!(stats.max_consolidated_roots, 99);
+    assert_eq!((stats.cuts, stats.cascading_cuts, stats.marked), (0, 0, 0));
+
+    while heap.pop().is_some() {
+        heap.check_invariants();
+    }
+    let stats = heap.stats();
+    assert_eq!((stats.roots, stats.marked), (0, 0));
+    // every pop consolidates, except for the last one, which leaves the heap empty
+    assert_eq!(stats.consolidations, 99);
+    assert!(stats.max_consolidated_roots >= 6);
+}
+
+#[test]
+fn test_cuts_and_cascading_cuts() {
+    let mut heap = FibonacciHeap::new();
+    let handles: Vec<_> = (0..17).map(|i| heap.push(i)).collect();
+    heap.pop();
+    // a single binomial tree of degree 4 with root 1
+    assert_eq!(heap.stats().roots, 1);
+
+    // cut leaves from the deepest path until cuts cascade
+    let mut previous = heap.stats();
+    for (i, handle) in handles.iter().enumerate().skip(1).rev() {
+        heap.decrease_key(handle, -(i as i32));
+        heap.check_invariants();
+        let stats = heap.stats();
+        assert!(stats.cuts >= previous.cuts);
+        assert!(stats.cascading_cuts <= stats.cuts);
+        previous = stats;
+    }
+    let stats = heap.stats();
+    assert!(stats.cascading_cuts > 0);
+    // all nodes are roots now, except for the ones that were never cut
+    assert_eq!(stats.cuts as usize + 1, stats.roots);
+    assert_eq!(stats.potential(), stats.roots + 2 * stats.marked);
+}
+
+#[test]
+fn test_delete() {
+    let mut heap = FibonacciHeap::new();
+    let handles: Vec<_> = (0..8).map(|i| heap.push(i)).collect();
+    heap.pop();
+    let before = heap.stats();
+    assert_eq!(heap.delete(handles[7].clone()), Some(7));
+    let after = heap.stats();
+    assert_eq!(after.cuts, before.cuts + 1);
+    assert_eq!(after.consolidations, before.consolidations + 1);
+    heap.check_invariants();
+}
+
+#[test]
+fn test_reset() {
+    let mut heap = FibonacciHeap::new();
+    let handles: Vec<_> = (0..20).map(|i| heap.push(i)).collect();
+    heap.pop();
+    heap.decrease_key(&handles[19], -1);
+    let stats = heap.stats();
+    assert!(stats.links > 0 && stats.cuts > 0 && stats.marked > 0);
+
+    heap.reset_stats();
+    let reset = heap.stats();
+    assert_eq!((reset.links, reset.cuts, reset.consolidations), (0, 0, 0));
+    assert_eq!((reset.max_degree, reset.max_consolidated_roots), (0, 0));
+    assert_eq!((reset.roots, reset.marked), (stats.roots, stats.marked));
+    assert_eq!(reset.potential(), stats.potential());
+
+    heap.pop();
+    assert_eq!(heap.stats().consolidations, 1);
+    heap.check_invariants();
+}
+
+#[test]
+fn test_meld() {
+    let mut heap1 = FibonacciHeap::new();
+    let mut heap2 = FibonacciHeap::new();
+    for i in 0..10 {
+        heap1.push(i);
+        heap2.push(i + 10);
+    }
+    heap1.pop();
+    heap2.pop();
+    let (stats1, stats2) = (heap1.stats(), heap2.stats());
+
+    let heap = FibonacciHeap::from_meld(heap1, heap2);
+    let stats = heap.stats();
+    assert_eq!(stats.roots, stats1.roots + stats2.roots);
+    assert_eq!(stats.links, stats1.links);
+    heap.check_invariants();
+
+    // melding into an empty heap keeps the counters of the empty heap
+    let heap = FibonacciHeap::from_meld(FibonacciHeap::new(), heap);
+    assert_eq!(heap.stats().links, 0);
+    assert_eq!(heap.stats().roots, stats.roots);
+    heap.check_invariants();
+}
+
+#[test]
+fn test_snapshot() {
+    let mut heap = FibonacciHeap::new();
+    let handles: Vec<_> = (0..30u32).map(|i| heap.push(i + 1)).collect();
+    heap.pop();
+    heap.decrease_key(&handles[29], 0);
+    heap.decrease_key(&handles[28], 0);
+    let mut bytes = vec![];
+    heap.write_snapshot(&mut bytes).unwrap();
+
+    let restored = FibonacciHeap::<u32>::read_snapshot(&bytes[..]).unwrap();
+    let stats = restored.stats();
+    assert_eq!(
+        (stats.roots, stats.marked),
+        (heap.stats().roots, heap.stats().marked)
+    );
+    assert_eq!(stats.max_degree, 4);
+    assert_eq!((stats.links, stats.cuts), (0, 0));
+    restored.check_invariants();
+}
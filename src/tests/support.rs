@@ -0,0 +1,63 @@
+//! Helpers shared by the tests.
+
+use std::cell::Cell;
+use std::cmp::Ordering;
+
+use crate::FibonacciHeap;
+
+// the benchmarks include this file too, so it must not depend on the crate
+mod rng;
+
+pub(super) use rng::Rng;
+
+thread_local! {
+    /// The number of comparisons that have been made.
+    static COMPARISONS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// A key that counts how often it is compared.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) struct Counted(pub(super) u64);
+
+impl Ord for Counted {
+    fn cmp(&self, other: &Counted) -> Ordering {
+        COMPARISONS.set(COMPARISONS.get() + 1);
+        self.0.cmp(&other.0)
+    }
+}
+
+impl PartialOrd for Counted {
+    fn partial_cmp(&self, other: &Counted) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Returns the number of comparisons that `f` makes.
+pub(super) fn comparisons<R>(f: impl FnOnce() -> R) -> usize {
+    COMPARISONS.set(0);
+    f();
+    COMPARISONS.get()
+}
+
+/// Builds a heap with trees, cut nodes and marks.
+pub(super) fn heap_with_trees() -> FibonacciHeap<i32> {
+    let mut heap = FibonacciHeap::new();
+    let handles: Vec<_> = (0..60).map(|i| heap.push(i * 7 % 60)).collect();
+    heap.pop();
+    for (i, handle) in handles.iter().enumerate().skip(3).step_by(5) {
+        heap.decrease_key(handle, -(i as i32));
+    }
+    heap
+}
+
+/// Returns the key, degree and mark of every node of `heap`, in preorder.
+pub(super) fn preorder<T: Ord + Clone>(heap: &FibonacciHeap<T>) -> Vec<(T, usize, bool)> {
+    let mut nodes = vec![];
+    heap.for_each_in_preorder(|key, degree, mark| nodes.push((key.clone(), degree, mark)));
+    nodes
+}
+
+/// The number of trees in `heap`.
+pub(super) fn roots<T>(heap: &FibonacciHeap<T>) -> usize {
+    heap.settled + heap.unsettled
+}
@@ -0,0 +1,98 @@
+//! Tests for the `sync` feature. They are small enough to be run under Miri:
+//!
+//! ```sh
+//! cargo +nightly miri test --features sync
+//! ```
+
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::{FibonacciHeap, NodePtr};
+
+#[test]
+fn test_heap_and_handles_are_send_and_sync() {
+    fn assert_send_sync<S: Send + Sync>() {}
+    assert_send_sync::<FibonacciHeap<String>>();
+    assert_send_sync::<NodePtr<String>>();
+}
+
+#[test]
+fn test_move_heap_to_other_thread() {
+    let mut fh: FibonacciHeap<String> = FibonacciHeap::new();
+    fh.push("c".to_string());
+    fh.push("a".to_string());
+    fh.push("b".to_string());
+    let popped = thread::spawn(move || Vec::from(fh)).join().unwrap();
+    assert_eq!(popped, vec!["a", "b", "c"]);
+}
+
+#[test]
+fn test_move_heap_and_handles_to_other_thread() {
+    let mut fh: FibonacciHeap<i32> = FibonacciHeap::new();
+    fh.push(5);
+    let ptr1 = fh.push(10);
+    let ptr2 = fh.push(15);
+    fh.push(20);
+    assert_eq!(fh.pop(), Some(5));
+    let mut fh = thread::spawn(move || {
+        fh.decrease_key(&ptr2, 1);
+        assert_eq!(fh.delete(ptr1), Some(10));
+        fh
+    })
+    .join()
+    .unwrap();
+    assert_eq!(fh.pop(), Some(1));
+    assert_eq!(fh.pop(), Some(20));
+    assert_eq!(fh.pop(), None);
+}
+
+#[test]
+fn test_handles_are_invalidated_across_threads() {
+    let mut fh: FibonacciHeap<i32> = FibonacciHeap::new();
+    let ptr = fh.push(1);
+    let ptr_clone = ptr.clone();
+    thread::spawn(move || drop(fh)).join().unwrap();
+    let mut other: FibonacciHeap<i32> = FibonacciHeap::new();
+    assert_eq!(other.delete(ptr), None); // invalidated: nothing happens
+    drop(ptr_clone);
+}
+
+#[test]
+fn test_push_from_several_threads() {
+    let fh: Arc<Mutex<FibonacciHeap<usize>>> = Arc::new(Mutex::new(FibonacciHeap::new()));
+    let threads: Vec<_> = (0..4)
+        .map(|t| {
+            let fh = Arc::clone(&fh);
+            thread::spawn(move || {
+                for i in 0..10 {
+                    let ptr = fh.lock().unwrap().push(100 + 10 * t + i);
+                    if i % 2 == 0 {
+                        fh.lock().unwrap().decrease_key(&ptr, 10 * t + i);
+                    }
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    let fh = Arc::try_unwrap(fh).ok().unwrap().into_inner().unwrap();
+    let mut expected: Vec<usize> = (0..40)
+        .map(|x| if x % 2 == 0 { x } else { 100 + x })
+        .collect();
+    expected.sort();
+    assert_eq!(Vec::from(fh), expected);
+}
+
+#[test]
+fn test_share_heap_between_threads() {
+    let fh: FibonacciHeap<i32> = FibonacciHeap::from([3, 1, 2]);
+    thread::scope(|s| {
+        for _ in 0..2 {
+            s.spawn(|| {
+                assert_eq!(fh.len(), 3);
+                assert!(!fh.is_empty());
+            });
+        }
+    });
+}
@@ -0,0 +1,105 @@
+use std::time::{Duration, Instant};
+
+use crate::TimerQueue;
+
+fn secs(start: Instant, secs: u64) -> Instant {
+    start + Duration::from_secs(secs)
+}
+
+#[test]
+fn test_expired_in_order() {
+    let start = Instant::now();
+    let mut timers = TimerQueue::new();
+    for (deadline, payload) in [(5, 'a'), (1, 'b'), (3, 'c'), (1, 'd'), (9, 'e')] {
+        timers.insert(secs(start, deadline), payload);
+    }
+    let expired: Vec<_> = timers.expired(secs(start, 3)).map(|(_, p)| p).collect();
+    assert_eq!(expired, vec!['b', 'd', 'c']);
+    assert_eq!(timers.len(), 2);
+    assert_eq!(timers.next_deadline(), Some(secs(start, 5)));
+    assert_eq!(timers.expired(secs(start, 4)).count(), 0);
+    let expired: Vec<_> = timers.expired(secs(start, 100)).collect();
+    assert_eq!(expired, vec![(secs(start, 5), 'a'), (secs(start, 9), 'e')]);
+    assert_eq!(timers.next_deadline(), None);
+}
+
+#[test]
+fn test_partially_consumed_expired() {
+    let start = Instant::now();
+    let mut timers = TimerQueue::new();
+    for i in 0..5 {
+        timers.insert(secs(start, i), i);
+    }
+    assert_eq!(timers.expired(secs(start, 10)).next(), Some((start, 0)));
+    assert_eq!(timers.len(), 4);
+}
+
+#[test]
+fn test_cancel() {
+    let start = Instant::now();
+    let mut timers = TimerQueue::new();
+    let a = timers.insert(secs(start, 1), "a");
+    let b = timers.insert(secs(start, 2), "b");
+    assert_eq!(timers.cancel(a), Some("a"));
+    assert_eq!(timers.cancel(a), None);
+    assert_eq!(timers.next_deadline(), Some(secs(start, 2)));
+    assert_eq!(timers.expired(secs(start, 2)).count(), 1);
+    assert_eq!(timers.cancel(b), None);
+    assert!(!timers.extend(b, secs(start, 5)));
+}
+
+#[test]
+fn test_extend() {
+    let start = Instant::now();
+    let mut timers = TimerQueue::new();
+    let a = timers.insert(secs(start, 1), "a");
+    let b = timers.insert(secs(start, 2), "b");
+    timers.insert(secs(start, 3), "c");
+    // later, then earlier again, then equal: the handle stays valid all the time
+    assert!(timers.extend(a, secs(start, 10)));
+    assert_eq!(timers.next_deadline(), Some(secs(start, 2)));
+    assert!(timers.extend(a, secs(start, 3)));
+    assert!(timers.extend(a, secs(start, 3)));
+    assert!(timers.extend(b, secs(start, 3)));
+    assert_eq!(timers.len(), 3);
+    // all three expire at the same time, in order of insertion
+    let expired: Vec<_> = timers.expired(secs(start, 3)).map(|(_, p)| p).collect();
+    assert_eq!(expired, vec!["a", "b", "c"]);
+}
+
+#[test]
+fn test_many_timers() {
+    let start = Instant::now();
+    let mut timers = TimerQueue::new();
+    let handles: Vec<_> = (0..1000)
+        .map(|i| timers.insert(secs(start, i), i))
+        .collect();
+    // extend every third timer by 1000 seconds, and cancel every fifth
+    for (i, &handle) in handles.iter().enumerate() {
+        if i % 3 == 0 {
+            timers.extend(handle, secs(start, i as u64 + 1000));
+        }
+        if i % 5 == 0 {
+            timers.cancel(handle);
+        }
+    }
+    let expired: Vec<_> = timers.expired(secs(start, 999)).map(|(_, i)| i).collect();
+    let expected: Vec<_> = (0..1000).filter(|i| i % 3 != 0 && i % 5 != 0).collect();
+    assert_eq!(expired, expected);
+    let rest: Vec<_> = timers
+        .expired(secs(start, 10_000))
+        .map(|(_, i)| i)
+        .collect();
+    let expected: Vec<_> = (0..1000).filter(|i| i % 3 == 0 && i % 5 != 0).collect();
+    assert_eq!(rest, expected);
+}
+
+#[test]
+#[should_panic(expected = "another timer queue")]
+fn test_handle_of_other_queue() {
+    let mut timers1 = TimerQueue::new();
+    let mut timers2 = TimerQueue::new();
+    let handle = timers1.insert(Instant::now(), ());
+    timers2.insert(Instant::now(), ());
+    timers2.cancel(handle);
+}
@@ -0,0 +1,164 @@
+//! Tests of recording and replaying traces.
+
+use super::support::{preorder, Rng};
+use crate::{RecordedPtr, RecordingHeap, ReplayError, Trace, TraceOp};
+
+/// Runs a pseudorandom workload on a few recording heaps that get melded now and then, and returns
+/// the resulting heap.
+fn record_workload(seed: u64, steps: usize) -> RecordingHeap<u32> {
+    let mut rng = Rng(seed);
+    let mut heaps: Vec<RecordingHeap<u32>> = (0..3).map(|_| RecordingHeap::new()).collect();
+    let mut handles: Vec<Vec<RecordedPtr<u32>>> = vec![vec![]; 3];
+    for _ in 0..steps {
+        let h = rng.below(3);
+        match rng.below(10) {
+            0..=3 => {
+                let key = rng.below(1000) as u32 + 1000;
+                handles[h].push(heaps[h].push(key));
+            }
+            4 | 5 => {
+                heaps[h].pop();
+            }
+            6 | 7 if !handles[h].is_empty() => {
+                let i = rng.below(handles[h].len());
+                let handle = &handles[h][i];
+                let key = heaps[h].len() as u32;
+                heaps[h].decrease_key(handle, key);
+            }
+            8 if !handles[h].is_empty() => {
+                let i = rng.below(handles[h].len());
+                let handle = handles[h].swap_remove(i);
+                heaps[h].delete(handle);
+            }
+            9 => {
+                let other = (h + 1 + rng.below(2)) % 3;
+                let (into, from) = (
+                    std::mem::take(&mut heaps[h]),
+                    std::mem::take(&mut heaps[other]),
+                );
+                heaps[h] = RecordingHeap::from_meld(into, from);
+                let moved = std::mem::take(&mut handles[other]);
+                handles[h].extend(moved);
+            }
+            _ => {}
+        }
+    }
+    let last = heaps.pop().unwrap();
+    let rest = heaps
+        .into_iter()
+        .fold(RecordingHeap::new(), RecordingHeap::from_meld);
+    RecordingHeap::from_meld(rest, last)
+}
+
+#[test]
+fn test_replay_reproduces_the_heap() {
+    for seed in 1..=if cfg!(miri) { 2 } else { 20 } {
+        let (heap, trace) = record_workload(seed, 500).into_parts();
+        assert!(trace.ops.iter().any(|op| matches!(op, TraceOp::Meld(_))));
+        let replayed = trace.replay().unwrap();
+        replayed.check_invariants();
+        assert_eq!(preorder(&replayed), preorder(&heap));
+        assert_eq!(Vec::from(replayed), Vec::from(heap));
+    }
+}
+
+#[test]
+fn test_replay_with_handles() {
+    let mut heap = RecordingHeap::new();
+    let a = heap.push(10);
+    let b = heap.push(20);
+    heap.pop();
+    let (_, trace) = heap.into_parts();
+    let (mut replayed, handles) = trace.replay_with_handles().unwrap();
+    assert_eq!(handles.len(), 2);
+    replayed.decrease_key(&handles[&b.id()], 5);
+    assert_eq!(replayed.delete(handles[&a.id()].clone()), None);
+    assert_eq!(Vec::from(replayed), vec![5]);
+}
+
+#[test]
+fn test_meld_into_empty_heap_is_not_nested() {
+    let mut heap = RecordingHeap::new();
+    heap.push(1);
+    let heap = RecordingHeap::from_meld(RecordingHeap::new(), heap);
+    assert_eq!(heap.trace().ops.len(), 1);
+    assert!(matches!(heap.trace().ops[0], TraceOp::Push { key: 1, .. }));
+
+    let mut other = RecordingHeap::new();
+    other.push(2);
+    let heap = RecordingHeap::from_meld(heap, other);
+    assert!(matches!(&heap.trace().ops[1], TraceOp::Meld(trace) if trace.ops.len() == 1));
+}
+
+#[test]
+fn test_deeply_nested_melds() {
+    let mut heap = RecordingHeap::new();
+    for i in 0..200 {
+        let mut outer = RecordingHeap::new();
+        outer.push(i);
+        heap = RecordingHeap::from_meld(outer, heap);
+    }
+    let (heap, trace) = heap.into_parts();
+    assert_eq!(preorder(&trace.replay().unwrap()), preorder(&heap));
+}
+
+#[test]
+fn test_stale_handle_of_another_heap() {
+    // a handle to a popped element is ignored by every heap, so this must replay as well
+    let mut heap1 = RecordingHeap::new();
+    let stale = heap1.push(1);
+    heap1.pop();
+    let mut heap2 = RecordingHeap::new();
+    heap2.push(2);
+    heap2.decrease_key(&stale, 0);
+    assert_eq!(heap2.delete(stale), None);
+    let heap = RecordingHeap::from_meld(heap1, heap2);
+    let (heap, trace) = heap.into_parts();
+    assert_eq!(Vec::from(trace.replay().unwrap()), Vec::from(heap));
+}
+
+#[test]
+fn test_invalid_traces() {
+    let push = |id, key| TraceOp::Push { id, key };
+    let cases = [
+        (vec![TraceOp::Delete { id: 7 }], ReplayError::UnknownId(7)),
+        (vec![push(1, 5), push(1, 6)], ReplayError::DuplicateId(1)),
+        (
+            vec![
+                TraceOp::Meld(Trace {
+                    ops: vec![push(1, 5), push(2, 6)],
+                }),
+                push(3, 1),
+                TraceOp::Meld(Trace {
+                    ops: vec![TraceOp::DecreaseKey { id: 3, key: 0 }],
+                }),
+            ],
+            ReplayError::ForeignId(3),
+        ),
+    ];
+    for (ops, error) in cases {
+        let Err(e) = (Trace { ops }).replay() else {
+            panic!("an invalid trace was replayed");
+        };
+        assert_eq!(e, error);
+    }
+
+    // ids of melded heaps may be used after the meld
+    let ops = vec![
+        TraceOp::Meld(Trace {
+            ops: vec![push(1, 5), push(2, 6)],
+        }),
+        TraceOp::DecreaseKey { id: 2, key: 0 },
+    ];
+    assert_eq!(Vec::from((Trace { ops }).replay().unwrap()), vec![0, 5]);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serialize_trace() {
+    let (heap, trace) = record_workload(7, 200).into_parts();
+    let json = serde_json::to_string(&trace).unwrap();
+    let deserialized: Trace<u32> = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized, trace);
+    assert_eq!(preorder(&deserialized.replay().unwrap()), preorder(&heap));
+}
@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+
+use crate::{FibonacciHeap, NodePtr};
+
+/// A set of timers with deadlines and payloads, such as the timeouts of network connections.
+///
+/// Timers can be cancelled, and their deadlines can be moved (usually extended, for example when a
+/// connection sees some activity). Timers with the same deadline expire in the order in which they
+/// were inserted.
+///
+/// # Examples
+///
+/// ```
+/// use fibonacci_heap_rust::TimerQueue;
+/// use std::time::{Duration, Instant};
+///
+/// let start = Instant::now();
+/// let mut timers = TimerQueue::new();
+/// let idle = timers.insert(start + Duration::from_secs(30), "idle timeout");
+/// timers.insert(start + Duration::from_secs(10), "handshake timeout");
+///
+/// // the connection saw some activity, so push the idle timeout back
+/// timers.extend(idle, start + Duration::from_secs(60));
+/// assert_eq!(timers.next_deadline(), Some(start + Duration::from_secs(10)));
+///
+/// let expired: Vec<_> = timers.expired(start + Duration::from_secs(45)).collect();
+/// assert_eq!(expired, vec![(start + Duration::from_secs(10), "handshake timeout")]);
+/// assert_eq!(timers.cancel(idle), Some("idle timeout"));
+/// assert!(timers.is_empty());
+/// ```
+pub struct TimerQueue<T> {
+    /// The deadlines of all timers, along with their ids.
+    heap: FibonacciHeap<(Instant, u64)>,
+    /// All timers, by id.
+    timers: HashMap<u64, Timer<T>>,
+    /// The id of the next timer that is inserted.
+    next_id: u64,
+    /// A number that is unique for every queue, to recognize handles of other queues.
+    queue_id: u64,
+}
+
+struct Timer<T> {
+    deadline: Instant,
+    /// The handle to the `(deadline, id)` entry of this timer in the heap.
+    ptr: NodePtr<(Instant, u64)>,
+    payload: T,
+}
+
+/// A handle to a timer in a [TimerQueue], which can be used to cancel it or to move its deadline.
+///
+/// Unlike a [NodePtr], this stays valid when the deadline of the timer is extended (which means
+/// that the timer is deleted from the heap and pushed again).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimerHandle {
+    id: u64,
+    queue_id: u64,
+}
+
+impl<T> TimerQueue<T> {
+    /// Constructs a new, empty timer queue.
+    pub fn new() -> TimerQueue<T> {
+        static NEXT_QUEUE_ID: AtomicU64 = AtomicU64::new(0);
+        TimerQueue {
+            heap: FibonacciHeap::new(),
+            timers: HashMap::new(),
+            next_id: 0,
+            queue_id: NEXT_QUEUE_ID.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+
+    /// Returns the number of timers.
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    /// Checks whether there are no timers.
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// Returns the earliest deadline of all timers, or `None` if there are no timers.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.heap.peek().map(|&(deadline, _)| deadline)
+    }
+
+    /// Adds a timer with the given deadline and payload, and returns a handle to it.
+    pub fn insert(&mut self, deadline: Instant, payload: T) -> TimerHandle {
+        let id = self.next_id;
+        self.next_id += 1;
+        let ptr = self.heap.push((deadline, id));
+        let timer = Timer {
+            deadline,
+            ptr,
+            payload,
+        };
+        self.timers.insert(id, timer);
+        TimerHandle {
+            id,
+            queue_id: self.queue_id,
+        }
+    }
+
+    /// Removes a timer, and returns its payload, or `None` if it has already expired or has been
+    /// cancelled.
+    ///
+    /// Panics if `handle` belongs to another timer queue.
+    pub fn cancel(&mut self, handle: TimerHandle) -> Option<T> {
+        self.check_handle(handle);
+        let timer = self.timers.remove(&handle.id)?;
+        self.heap.delete(timer.ptr);
+        Some(timer.payload)
+    }
+
+    /// Moves the deadline of a timer to `new_deadline`. Returns false (and does nothing) if the
+    /// timer has already expired or has been cancelled.
+    ///
+    /// Moving a deadline forward is a decrease_key. The heap cannot increase keys, so a deadline
+    /// that is moved back (which is the common case) is deleted and pushed again, which takes
+    /// O(log n) amortized time. Either way, timers with the same deadline still expire in the order
+    /// in which they were inserted.
+    ///
+    /// Panics if `handle` belongs to another timer queue.
+    pub fn extend(&mut self, handle: TimerHandle, new_deadline: Instant) -> bool {
+        self.check_handle(handle);
+        let Some(timer) = self.timers.get_mut(&handle.id) else {
+            return false;
+        };
+        if new_deadline < timer.deadline {
+            self.heap
+                .decrease_key(&timer.ptr, (new_deadline, handle.id));
+        } else if new_deadline > timer.deadline {
+            self.heap.delete(timer.ptr.clone());
+            timer.ptr = self.heap.push((new_deadline, handle.id));
+        }
+        timer.deadline = new_deadline;
+        true
+    }
+
+    /// Removes all timers whose deadline is at or before `now`, and returns their deadlines and
+    /// payloads, in order of deadline.
+    ///
+    /// The timers are removed one by one while the iterator is consumed; the ones that are not
+    /// consumed stay in the queue.
+    pub fn expired(&mut self, now: Instant) -> impl Iterator<Item = (Instant, T)> + '_ {
+        std::iter::from_fn(move || {
+            if self.next_deadline()? > now {
+                return None;
+            }
+            let (deadline, id) = self.heap.pop()?;
+            let timer = self.timers.remove(&id)?;
+            Some((deadline, timer.payload))
+        })
+    }
+
+    fn check_handle(&self, handle: TimerHandle) {
+        if handle.queue_id != self.queue_id {
+            panic!("this timer handle belongs to another timer queue");
+        }
+    }
+}
+
+impl<T> Default for TimerQueue<T> {
+    /// Creates an empty `TimerQueue<T>`.
+    fn default() -> TimerQueue<T> {
+        TimerQueue::new()
+    }
+}
@@ -0,0 +1,319 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::{FibonacciHeap, NodePtr};
+
+/// A [FibonacciHeap] that records every operation applied to it in a [Trace], which can be
+/// replayed later to reproduce exactly the same heap, for example to debug or benchmark a workload
+/// that was slow in production.
+///
+/// Handles are recorded by id: every element that is pushed gets an id that is unique in the whole
+/// process, and the [RecordedPtr] that is returned carries that id along with the [NodePtr].
+///
+/// # Examples
+///
+/// ```
+/// use fibonacci_heap_rust::RecordingHeap;
+///
+/// let mut heap = RecordingHeap::new();
+/// heap.push(3);
+/// let five = heap.push(5);
+/// heap.decrease_key(&five, 1);
+/// assert_eq!(heap.pop(), Some(1));
+///
+/// let (heap, trace) = heap.into_parts();
+/// let replayed = trace.replay().unwrap();
+/// assert_eq!(Vec::from(replayed), Vec::from(heap));
+/// ```
+pub struct RecordingHeap<T> {
+    heap: FibonacciHeap<T>,
+    trace: Trace<T>,
+}
+
+/// A handle to an element in a [RecordingHeap]: a [NodePtr] along with the id of the element in
+/// the trace.
+pub struct RecordedPtr<T> {
+    ptr: NodePtr<T>,
+    id: u64,
+}
+
+/// The operations that were applied to a [RecordingHeap], in order, starting from an empty heap.
+///
+/// With the `serde` feature enabled, a trace implements `Serialize` and `Deserialize` (if `T`
+/// does), so that it can be saved and replayed somewhere else.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Trace<T> {
+    /// The operations, oldest first.
+    pub ops: Vec<TraceOp<T>>,
+}
+
+/// A heap along with handles to its elements by id, as returned by
+/// [replay_with_handles](Trace::replay_with_handles).
+pub type HeapWithHandlesById<T> = (FibonacciHeap<T>, HashMap<u64, NodePtr<T>>);
+
+/// A single operation in a [Trace].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TraceOp<T> {
+    /// `key` was pushed, and got the handle with id `id`.
+    Push { id: u64, key: T },
+    /// The minimum was popped.
+    Pop,
+    /// The key of the element with id `id` was decreased to `key`.
+    DecreaseKey { id: u64, key: T },
+    /// The element with id `id` was deleted.
+    Delete { id: u64 },
+    /// Another heap was melded into this one; the trace is the history of that other heap.
+    Meld(Trace<T>),
+}
+
+/// The error returned when a [Trace] cannot be replayed, which only happens if it was not recorded
+/// by a [RecordingHeap] (for example, if it was edited by hand).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplayError {
+    /// An operation uses an id that was never pushed.
+    UnknownId(u64),
+    /// Two pushes have the same id.
+    DuplicateId(u64),
+    /// An operation uses the id of an element of another heap, which was not melded into this one.
+    ForeignId(u64),
+}
+
+impl<T: Ord> RecordingHeap<T> {
+    /// Constructs a new, empty heap with an empty trace.
+    pub const fn new() -> RecordingHeap<T> {
+        RecordingHeap {
+            heap: FibonacciHeap::new(),
+            trace: Trace { ops: Vec::new() },
+        }
+    }
+
+    /// Returns the number of elements in the heap.
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    /// Checks whether the heap is empty.
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// Returns the operations recorded so far.
+    pub fn trace(&self) -> &Trace<T> {
+        &self.trace
+    }
+
+    /// Stops recording, and returns the heap and its trace.
+    pub fn into_parts(self) -> (FibonacciHeap<T>, Trace<T>) {
+        (self.heap, self.trace)
+    }
+
+    /// Produces a heap from melding two heaps, like [FibonacciHeap::from_meld]. The trace of
+    /// `heap2` is recorded as a [Meld](TraceOp::Meld) in the trace of `heap1`.
+    pub fn from_meld(mut heap1: RecordingHeap<T>, heap2: RecordingHeap<T>) -> RecordingHeap<T> {
+        let heap = FibonacciHeap::from_meld(heap1.heap, heap2.heap);
+        let trace = if heap1.trace.ops.is_empty() {
+            // nothing happened to the first heap, so don't nest the trace of the second one
+            heap2.trace
+        } else {
+            heap1.trace.ops.push(TraceOp::Meld(heap2.trace));
+            heap1.trace
+        };
+        RecordingHeap { heap, trace }
+    }
+
+    /// Returns a reference to the minimum element, like [FibonacciHeap::peek]. This is not
+    /// recorded, since it does not change the heap.
+    pub fn peek(&self) -> Option<&T> {
+        self.heap.peek()
+    }
+
+    /// Extracts the minimum element, like [FibonacciHeap::pop].
+    pub fn pop(&mut self) -> Option<T> {
+        let popped = self.heap.pop();
+        self.trace.ops.push(TraceOp::Pop);
+        popped
+    }
+
+    /// Deletes the element that `elem` points to, like [FibonacciHeap::delete].
+    pub fn delete(&mut self, elem: RecordedPtr<T>) -> Option<T> {
+        let deleted = self.heap.delete(elem.ptr);
+        self.trace.ops.push(TraceOp::Delete { id: elem.id });
+        deleted
+    }
+}
+
+impl<T: Ord + Clone> RecordingHeap<T> {
+    /// Inserts an element, like [FibonacciHeap::push]. The key is cloned into the trace.
+    pub fn push(&mut self, key: T) -> RecordedPtr<T> {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let ptr = self.heap.push(key.clone());
+        self.trace.ops.push(TraceOp::Push { id, key });
+        RecordedPtr { ptr, id }
+    }
+
+    /// Decreases the key of the element that `elem` points to, like
+    /// [FibonacciHeap::decrease_key]. The new key is cloned into the trace.
+    pub fn decrease_key(&mut self, elem: &RecordedPtr<T>, key: T) {
+        self.heap.decrease_key(&elem.ptr, key.clone());
+        self.trace
+            .ops
+            .push(TraceOp::DecreaseKey { id: elem.id, key });
+    }
+}
+
+impl<T: Ord> Default for RecordingHeap<T> {
+    /// Creates an empty `RecordingHeap<T>`.
+    fn default() -> RecordingHeap<T> {
+        RecordingHeap::new()
+    }
+}
+
+impl<T> RecordedPtr<T> {
+    /// Returns the id of the element in the trace.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Returns the plain handle to the element.
+    pub fn ptr(&self) -> &NodePtr<T> {
+        &self.ptr
+    }
+}
+
+impl<T> Clone for RecordedPtr<T> {
+    fn clone(&self) -> RecordedPtr<T> {
+        RecordedPtr {
+            ptr: self.ptr.clone(),
+            id: self.id,
+        }
+    }
+}
+
+impl<T: Ord + Clone> Trace<T> {
+    /// Replays the trace against a new, empty heap, and returns the result. Since the heap is
+    /// deterministic, this is exactly the same heap (with the same trees and marks) as the one
+    /// that was recorded, as long as the `Ord` implementation of `T` is deterministic.
+    ///
+    /// The trace is validated while it is replayed: an error is returned (rather than a panic) if
+    /// it uses ids that were never pushed, that were pushed twice, or that belong to another heap.
+    pub fn replay(&self) -> Result<FibonacciHeap<T>, ReplayError> {
+        Ok(self.replay_with_handles()?.0)
+    }
+
+    /// Like [replay](Trace::replay), but also returns the handles to all elements that were pushed,
+    /// by id (including the ones that were popped or deleted since).
+    pub fn replay_with_handles(&self) -> Result<HeapWithHandlesById<T>, ReplayError> {
+        /// A trace that is being replayed into its own heap.
+        struct Frame<'a, T> {
+            ops: std::slice::Iter<'a, TraceOp<T>>,
+            heap: FibonacciHeap<T>,
+            /// The number of the heap, to check that ids are only used with their own heap.
+            number: usize,
+        }
+
+        // for every id, its handle and the number of the heap it was pushed into
+        let mut handles: HashMap<u64, (NodePtr<T>, usize)> = HashMap::new();
+        // for every heap number, the number of the heap it was melded into (or itself); this is a
+        // union-find structure, like `HeapTag`
+        let mut melded_into = vec![0];
+        // replay nested traces with an explicit stack, since they can be nested arbitrarily deep
+        let mut frames = vec![Frame {
+            ops: self.ops.iter(),
+            heap: FibonacciHeap::new(),
+            number: 0,
+        }];
+        loop {
+            let frame = frames.last_mut().unwrap();
+            let Some(op) = frame.ops.next() else {
+                let mut finished = frames.pop().unwrap();
+                let Some(parent) = frames.last_mut() else {
+                    let handles = handles.into_iter().map(|(id, (ptr, _))| (id, ptr));
+                    return Ok((finished.heap, handles.collect()));
+                };
+                parent.heap.absorb(&mut finished.heap);
+                melded_into[finished.number] = parent.number;
+                continue;
+            };
+            match op {
+                TraceOp::Push { id, key } => {
+                    if handles.contains_key(id) {
+                        return Err(ReplayError::DuplicateId(*id));
+                    }
+                    let ptr = frame.heap.push(key.clone());
+                    handles.insert(*id, (ptr, frame.number));
+                }
+                TraceOp::Pop => {
+                    frame.heap.pop();
+                }
+                TraceOp::DecreaseKey { id, .. } | TraceOp::Delete { id } => {
+                    let (ptr, number) = handles.get(id).ok_or(ReplayError::UnknownId(*id))?;
+                    // Handles to elements that are gone are ignored by the heap, whatever heap they
+                    // are used with. Otherwise, the heap would panic for foreign handles.
+                    if ptr.0.borrow().invalidated {
+                        continue;
+                    }
+                    if find(&mut melded_into, *number) != frame.number {
+                        return Err(ReplayError::ForeignId(*id));
+                    }
+                    match op {
+                        TraceOp::DecreaseKey { key, .. } => {
+                            frame.heap.decrease_key(ptr, key.clone());
+                        }
+                        _ => {
+                            frame.heap.delete(ptr.clone());
+                        }
+                    }
+                }
+                TraceOp::Meld(trace) => {
+                    let number = melded_into.len();
+                    melded_into.push(number);
+                    frames.push(Frame {
+                        ops: trace.ops.iter(),
+                        heap: FibonacciHeap::new(),
+                        number,
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Returns the number of the heap that heap `number` has been melded into, with path halving.
+fn find(melded_into: &mut [usize], mut number: usize) -> usize {
+    while melded_into[number] != number {
+        melded_into[number] = melded_into[melded_into[number]];
+        number = melded_into[number];
+    }
+    number
+}
+
+impl<T> Default for Trace<T> {
+    /// Creates an empty trace.
+    fn default() -> Trace<T> {
+        Trace { ops: Vec::new() }
+    }
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::UnknownId(id) => {
+                write!(f, "the trace uses id {id}, which was never pushed")
+            }
+            ReplayError::DuplicateId(id) => write!(f, "the trace pushes id {id} twice"),
+            ReplayError::ForeignId(id) => {
+                write!(
+                    f,
+                    "the trace uses id {id} with a heap that it does not belong to"
+                )
+            }
+        }
+    }
+}
+
+impl Error for ReplayError {}